@@ -0,0 +1,178 @@
+use crate::node::{parse, Node, NodeError};
+use crate::token::{tokenize, TokenKind};
+use std::collections::HashMap;
+
+impl Node {
+    pub fn is_linear_in(&self, var: &str) -> bool {
+        self.degree_in(var).is_some_and(|degree| degree <= 1)
+    }
+
+    pub(crate) fn degree_in(&self, var: &str) -> Option<u32> {
+        if !self.variables().contains(&var) {
+            return Some(0);
+        }
+        if !self.has_children() {
+            return Some(1);
+        }
+
+        let l_degree = || self.l_child.as_ref().map_or(Some(0), |l| l.degree_in(var));
+        let r_degree = || self.r_child.as_ref().map_or(Some(0), |r| r.degree_in(var));
+
+        match self.operator() {
+            "+" | "-" => Some(l_degree()?.max(r_degree()?)),
+            "*" => Some(l_degree()? + r_degree()?),
+            "/" if r_degree()? == 0 => l_degree(),
+            "^" if r_degree()? == 0 => {
+                let exponent = self.r_child.as_ref()?.evaluate().ok()?;
+                if exponent < 0.0 || exponent.fract() != 0.0 || exponent > u32::MAX as f64 {
+                    return None;
+                }
+                l_degree()?.checked_mul(exponent as u32)
+            }
+            _ => None,
+        }
+    }
+
+    pub fn as_polynomial(&self, var: &str) -> Option<Vec<f64>> {
+        self.degree_in(var)?;
+        let mut coefficients = self.coefficients(var)?;
+        while coefficients.len() > 1 && coefficients.last() == Some(&0.0) {
+            coefficients.pop();
+        }
+        Some(coefficients)
+    }
+
+    fn coefficients(&self, var: &str) -> Option<Vec<f64>> {
+        if !self.variables().contains(&var) {
+            return self.evaluate().ok().map(|constant| vec![constant]);
+        }
+        if !self.has_children() {
+            return Some(vec![0.0, 1.0]);
+        }
+
+        let r_child = self.r_child.as_ref()?.coefficients(var)?;
+        let Some(l_child) = &self.l_child else {
+            return match self.operator() {
+                "+" => Some(r_child),
+                "-" => Some(r_child.iter().map(|c| -c).collect()),
+                _ => None,
+            };
+        };
+        let l_child = l_child.coefficients(var)?;
+
+        match self.operator() {
+            "+" => Some(combine(&l_child, &r_child, |l, r| l + r)),
+            "-" => Some(combine(&l_child, &r_child, |l, r| l - r)),
+            "*" => Some(multiply(&l_child, &r_child)),
+            "/" if r_child.len() == 1 => Some(l_child.iter().map(|c| c / r_child[0]).collect()),
+            "^" if r_child.len() == 1 => {
+                let mut power = vec![1.0];
+                for _ in 0..r_child[0] as u32 {
+                    power = multiply(&power, &l_child);
+                }
+                Some(power)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn combine(l: &[f64], r: &[f64], operation: impl Fn(f64, f64) -> f64) -> Vec<f64> {
+    (0..l.len().max(r.len()))
+        .map(|i| {
+            operation(
+                l.get(i).copied().unwrap_or(0.0),
+                r.get(i).copied().unwrap_or(0.0),
+            )
+        })
+        .collect()
+}
+
+fn multiply(l: &[f64], r: &[f64]) -> Vec<f64> {
+    let mut product = vec![0.0; l.len() + r.len() - 1];
+    for (i, l) in l.iter().enumerate() {
+        for (j, r) in r.iter().enumerate() {
+            product[i + j] += l * r;
+        }
+    }
+    product
+}
+
+pub fn solve_linear(equation: &str, var: &str) -> Result<f64, NodeError> {
+    let equals = tokenize(equation)
+        .into_iter()
+        .filter(|token| token.kind == TokenKind::Assign)
+        .collect::<Vec<_>>();
+    let [equals] = equals.as_slice() else {
+        return Err(NodeError::InvalidExpression(
+            "an equation needs exactly one '='".to_string(),
+        ));
+    };
+
+    let difference = Node::binary(
+        "-",
+        parse(&equation[..equals.start])?,
+        parse(&equation[equals.end..])?,
+    );
+    if !difference.is_linear_in(var) {
+        return Err(NodeError::InvalidExpression(format!(
+            "'{}' is not linear in {}",
+            equation, var
+        )));
+    }
+
+    let at = |value: f64| difference.evaluate_with(&HashMap::from([(var.to_string(), value)]));
+    let b = at(0.0)?;
+    let a = at(1.0)? - b;
+    if a == 0.0 {
+        return Err(NodeError::DomainError(format!(
+            "'{}' has no unique solution for {}",
+            equation, var
+        )));
+    }
+
+    Ok(-b / a)
+}
+
+#[test]
+fn test_is_linear_in() {
+    let linear = |input: &str| Node::from_expression(input.to_string()).is_linear_in("x");
+    assert!(linear("2 * x + 3"));
+    assert!(linear("(x - y) / 4 + y ^ 2"));
+    assert!(linear("x ^ 1 - sin(y)"));
+    assert!(linear("5"));
+    assert!(!linear("x * x"));
+    assert!(!linear("sin(x)"));
+    assert!(!linear("2 ^ x"));
+    assert!(!linear("1 / x"));
+    assert!(!linear("x ^ 2 + 1"));
+}
+
+#[test]
+fn test_as_polynomial() {
+    let polynomial = |input: &str| parse(input).unwrap().as_polynomial("x");
+    assert_eq!(
+        polynomial("2 * x ^ 2 + 3 * x + 1"),
+        Some(vec![1.0, 3.0, 2.0])
+    );
+    assert_eq!(polynomial("(x - 1) ^ 2"), Some(vec![1.0, -2.0, 1.0]));
+    assert_eq!(
+        polynomial("-x / 2 + pi"),
+        Some(vec![std::f64::consts::PI, -0.5])
+    );
+    assert_eq!(polynomial("x * x - x ^ 2 + 4"), Some(vec![4.0]));
+    assert_eq!(polynomial("sqrt(4) * x"), Some(vec![0.0, 2.0]));
+    assert_eq!(polynomial("1 / x"), None);
+    assert_eq!(polynomial("sin(x)"), None);
+    assert_eq!(polynomial("x * y"), None);
+}
+
+#[test]
+fn test_solve_linear() {
+    assert!(solve_linear("2 * x + 4 = 10", "x").is_ok_and(|x| x == 3.0));
+    assert!(solve_linear("3 * (y - 1) = y + 5", "y").is_ok_and(|x| x == 4.0));
+    assert!(solve_linear("x * x = 4", "x").is_err());
+    assert!(solve_linear("x + 1 = x", "x").is_err());
+    assert!(solve_linear("x + 1", "x").is_err());
+    assert!(solve_linear("x == 1 = 2", "x").is_err());
+}
@@ -0,0 +1,41 @@
+use crate::format::format_result;
+use crate::node::parse;
+use std::io;
+use std::path::Path;
+
+pub fn evaluate_lines(contents: &str) -> Vec<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| match parse(line).and_then(|root| root.evaluate()) {
+            Ok(result) => format!("{} -> {}", line, format_result(result)),
+            Err(err) => format!("{} -> Error: {}", line, err),
+        })
+        .collect()
+}
+
+pub fn evaluate_file<P: AsRef<Path>>(path: P) -> io::Result<Vec<String>> {
+    Ok(evaluate_lines(&std::fs::read_to_string(path)?))
+}
+
+#[test]
+fn test_evaluate_lines() {
+    let results = evaluate_lines("1 + 2\n\n# a comment\n  2 * 4  \n1 / 0\n");
+    assert_eq!(
+        results,
+        vec![
+            "1 + 2 -> 3",
+            "2 * 4 -> 8",
+            "1 / 0 -> Error: Cannot divide by zero in '1 / 0'"
+        ]
+    );
+}
+
+#[test]
+fn test_evaluate_lines_with_windows_line_endings() {
+    assert_eq!(
+        evaluate_lines("1 + 2\r\n\r\n3 * 3\r\n"),
+        vec!["1 + 2 -> 3", "3 * 3 -> 9"]
+    );
+}
@@ -0,0 +1,86 @@
+use crate::node::{Node, NodeError, NodeValue};
+
+use num_bigint::BigInt;
+use num_traits::{FromPrimitive, ToPrimitive, Zero};
+
+impl Node {
+    pub fn evaluate_bigint(&self) -> Result<BigInt, NodeError> {
+        if !self.has_children() {
+            let integer = match self.value {
+                NodeValue::Number(value) if value.fract() == 0.0 => BigInt::from_f64(value),
+                _ => None,
+            };
+            return integer.ok_or_else(|| {
+                NodeError::InvalidExpression(format!("'{}' is not an integer", self.value))
+            });
+        }
+
+        let l_operand = match &self.l_child {
+            None => BigInt::zero(),
+            Some(l_child) => l_child.evaluate_bigint()?,
+        };
+        let r_operand = match &self.r_child {
+            None => BigInt::zero(),
+            Some(r_child) => r_child.evaluate_bigint()?,
+        };
+
+        match self.operator() {
+            "+" => Ok(l_operand + r_operand),
+            "-" => Ok(l_operand - r_operand),
+            "*" => Ok(l_operand * r_operand),
+            "/" if r_operand.is_zero() => Err(NodeError::DivideByZero {
+                expression: self.to_infix(),
+            }),
+            "/" if !(&l_operand % &r_operand).is_zero() => Err(NodeError::DomainError(format!(
+                "'{}' is not an exact division",
+                self.to_infix()
+            ))),
+            "/" => Ok(l_operand / r_operand),
+            "^" => r_operand
+                .to_u32()
+                .map(|exponent| l_operand.pow(exponent))
+                .ok_or_else(|| {
+                    NodeError::DomainError(format!(
+                        "integer exponents must be between 0 and {}, got {}",
+                        u32::MAX,
+                        r_operand
+                    ))
+                }),
+            operator => Err(NodeError::InvalidExpression(format!(
+                "'{}' is not supported in integer evaluation",
+                operator
+            ))),
+        }
+    }
+}
+
+#[test]
+fn test_bigint_power() {
+    let root = Node::from_expression("2 ^ 100".to_string());
+    assert_eq!(
+        root.evaluate_bigint().unwrap().to_string(),
+        "1267650600228229401496703205376"
+    );
+}
+
+#[test]
+fn test_bigint_arithmetic() {
+    let root = Node::from_expression("(10 ^ 30 + 7) * -3 - 1".to_string());
+    assert_eq!(
+        root.evaluate_bigint().unwrap().to_string(),
+        "-3000000000000000000000000000022"
+    );
+
+    let root = Node::from_expression("(3 ^ 50) / (3 ^ 48)".to_string());
+    assert_eq!(root.evaluate_bigint().unwrap(), BigInt::from(9));
+
+    assert!(Node::from_expression("7 / 2".to_string())
+        .evaluate_bigint()
+        .is_err());
+    assert!(Node::from_expression("1.5 * 2".to_string())
+        .evaluate_bigint()
+        .is_err());
+    assert!(Node::from_expression("2 ^ -1".to_string())
+        .evaluate_bigint()
+        .is_err());
+}
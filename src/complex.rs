@@ -0,0 +1,111 @@
+use crate::node::{constant, unquote, Node, NodeError, NodeValue};
+use num_complex::Complex;
+use std::collections::HashMap;
+
+fn power(base: Complex<f64>, exponent: Complex<f64>) -> Complex<f64> {
+    let zero = Complex::new(0.0, 0.0);
+    if base == zero {
+        return if exponent == zero {
+            Complex::new(1.0, 0.0)
+        } else {
+            base
+        };
+    }
+    base.powc(exponent)
+}
+
+pub fn to_polar(c: Complex<f64>) -> (f64, f64) {
+    c.to_polar()
+}
+
+impl Node {
+    pub fn evaluate_complex(
+        &self,
+        variables: &HashMap<String, Complex<f64>>,
+    ) -> Result<Complex<f64>, NodeError> {
+        if !self.has_children() {
+            return match &self.value {
+                NodeValue::Number(number) => Ok(Complex::new(*number, 0.0)),
+                NodeValue::Variable(name) => {
+                    let name = unquote(name);
+                    match (variables.get(name), constant(name)) {
+                        (Some(value), _) => Ok(*value),
+                        (None, _) if name == "i" => Ok(Complex::i()),
+                        (None, Some(value)) => Ok(Complex::new(value, 0.0)),
+                        (None, None) => Err(NodeError::UnknownVariable(name.to_string())),
+                    }
+                }
+                NodeValue::Operator(operator) => {
+                    Err(NodeError::InvalidExpression(operator.to_string()))
+                }
+            };
+        }
+
+        let r_operand = match &self.r_child {
+            None => Complex::new(0.0, 0.0),
+            Some(r_child) => r_child.evaluate_complex(variables)?,
+        };
+        let l_operand = match &self.l_child {
+            None => Complex::new(0.0, 0.0),
+            Some(l_child) => l_child.evaluate_complex(variables)?,
+        };
+
+        match self.operator() {
+            "+" => Ok(l_operand + r_operand),
+            "-" => Ok(l_operand - r_operand),
+            "*" => Ok(l_operand * r_operand),
+            "/" if r_operand == Complex::new(0.0, 0.0) => Err(NodeError::DivideByZero {
+                expression: self.to_infix(),
+            }),
+            "/" => Ok(l_operand / r_operand),
+            "^" => Ok(power(l_operand, r_operand)),
+            "sqrt" => Ok(r_operand.sqrt()),
+            "exp" => Ok(r_operand.exp()),
+            operator => Err(NodeError::InvalidExpression(format!(
+                "'{}' is not supported in complex evaluation",
+                operator
+            ))),
+        }
+    }
+}
+
+#[test]
+fn test_to_polar() {
+    let root = Node::from_expression("-1 + 0 * i".to_string());
+    let result = root.evaluate_complex(&HashMap::new()).unwrap();
+    assert_eq!(to_polar(result), (1.0, std::f64::consts::PI));
+
+    let (magnitude, phase) = to_polar(Complex::new(0.0, 2.0));
+    assert_eq!(magnitude, 2.0);
+    assert_eq!(phase, std::f64::consts::FRAC_PI_2);
+}
+
+#[test]
+fn test_evaluate_complex() {
+    let evaluate = |input: &str| {
+        Node::from_expression(input.to_string())
+            .evaluate_complex(&HashMap::from([("z".to_string(), Complex::new(1.0, 1.0))]))
+    };
+    assert_eq!(evaluate("i * i").unwrap(), Complex::new(-1.0, 0.0));
+    assert_eq!(
+        evaluate("(3 + 4 * i) / (1 - 2 * i)").unwrap(),
+        Complex::new(-1.0, 2.0)
+    );
+    assert_eq!(evaluate("z * z").unwrap(), Complex::new(0.0, 2.0));
+
+    let root = evaluate("sqrt(-4)").unwrap();
+    assert!(root.re.abs() < 1e-15 && root.im == 2.0);
+    assert!(evaluate("0 ^ 0").is_ok_and(|x| x == Complex::new(1.0, 0.0)));
+    assert!(evaluate("1 / (z - 1 - i)").is_err());
+    assert!(evaluate("sin(z)").is_err());
+}
+
+#[test]
+fn test_complex_quoted_variable() {
+    let variables = HashMap::from([("total cost".to_string(), Complex::new(1.0, 2.0))]);
+    let root = crate::node::parse("\"total cost\" * 2").unwrap();
+    assert_eq!(
+        root.evaluate_complex(&variables).unwrap(),
+        Complex::new(2.0, 4.0)
+    );
+}
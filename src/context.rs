@@ -0,0 +1,215 @@
+use crate::node::{parse, Node, NodeError, CONSTANTS};
+use std::collections::HashMap;
+
+pub struct Context {
+    constants: HashMap<String, f64>,
+    variables: HashMap<String, f64>,
+    strict_identifiers: bool,
+    leaf_parser: Option<LeafParser>,
+    rounding_mode: Option<RoundingMode>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RoundingMode {
+    TowardZero,
+    AwayFromZero,
+    HalfUp,
+    HalfEven,
+}
+
+impl RoundingMode {
+    pub fn round(self, value: f64) -> f64 {
+        match self {
+            RoundingMode::TowardZero => value.trunc(),
+            RoundingMode::AwayFromZero if value < 0.0 => value.floor(),
+            RoundingMode::AwayFromZero => value.ceil(),
+            RoundingMode::HalfUp => value.round(),
+            RoundingMode::HalfEven => value.round_ties_even(),
+        }
+    }
+}
+
+type LeafParser = Box<dyn Fn(&str) -> Option<f64>>;
+
+impl Default for Context {
+    fn default() -> Self {
+        Context {
+            constants: CONSTANTS
+                .iter()
+                .map(|(name, value)| (name.to_string(), *value))
+                .collect(),
+            variables: HashMap::new(),
+            strict_identifiers: false,
+            leaf_parser: None,
+            rounding_mode: None,
+        }
+    }
+}
+
+impl Context {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_constant(&mut self, name: &str, value: f64) {
+        self.constants.insert(name.to_string(), value);
+    }
+
+    pub fn set_variable(&mut self, name: &str, value: f64) {
+        self.variables.insert(name.to_string(), value);
+    }
+
+    pub fn set_strict_identifiers(&mut self, strict_identifiers: bool) {
+        self.strict_identifiers = strict_identifiers;
+    }
+
+    pub fn strict_identifiers(&self) -> bool {
+        self.strict_identifiers
+    }
+
+    pub fn set_leaf_parser(&mut self, leaf_parser: LeafParser) {
+        self.leaf_parser = Some(leaf_parser);
+    }
+
+    pub fn set_rounding_mode(&mut self, rounding_mode: Option<RoundingMode>) {
+        self.rounding_mode = rounding_mode;
+    }
+
+    pub fn rounding_mode(&self) -> Option<RoundingMode> {
+        self.rounding_mode
+    }
+
+    pub fn parse(&self, input: &str) -> Result<Node, NodeError> {
+        let root = match &self.leaf_parser {
+            Some(leaf_parser) => parse(&replace_literals(input, leaf_parser))?,
+            None => parse(input)?,
+        };
+        if self.strict_identifiers {
+            if let Some(name) = root.variables().into_iter().find(|name| {
+                !self.constants.contains_key(*name) && !self.variables.contains_key(*name)
+            }) {
+                return Err(NodeError::UnknownVariable(name.to_string()));
+            }
+        }
+        Ok(root)
+    }
+
+    pub fn constants(&self) -> &HashMap<String, f64> {
+        &self.constants
+    }
+
+    pub fn variables(&self) -> &HashMap<String, f64> {
+        &self.variables
+    }
+}
+
+fn replace_literals(input: &str, leaf_parser: &LeafParser) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut literal = String::new();
+    for c in input.chars().chain(std::iter::once(' ')) {
+        if !(c.is_whitespace() || c == '(' || c == ')' || c == ',') {
+            literal.push(c);
+            continue;
+        }
+
+        match leaf_parser(&literal) {
+            Some(value) if value.is_nan() => result.push_str("nan"),
+            Some(value) => result.push_str(&format!("({:?})", value)),
+            None => result.push_str(&literal),
+        }
+        literal.clear();
+        result.push(c);
+    }
+    result.pop();
+    result
+}
+
+#[test]
+fn test_custom_constant() {
+    let mut ctx = Context::new();
+    ctx.add_constant("g", 9.81);
+    ctx.set_variable("t", 2.0);
+
+    let root = Node::from_expression("g * t ^ 2 / 2".to_string());
+    assert!(root
+        .evaluate_with_context(&ctx)
+        .is_ok_and(|x| crate::approx_eq(x, 19.62, 1e-12)));
+    assert!(root.evaluate().is_err());
+
+    let root = Node::from_expression("2 * pi".to_string());
+    assert!(root
+        .evaluate_with_context(&ctx)
+        .is_ok_and(|x| x == std::f64::consts::TAU));
+}
+
+#[test]
+fn test_constants_shadow_variables() {
+    let mut ctx = Context::new();
+    ctx.set_variable("c", 1.0);
+    ctx.add_constant("c", 299_792_458.0);
+
+    let root = Node::from_expression("c".to_string());
+    assert!(root
+        .evaluate_with_context(&ctx)
+        .is_ok_and(|x| x == 299_792_458.0));
+}
+
+#[test]
+fn test_strict_identifiers() {
+    let mut ctx = Context::new();
+    ctx.set_variable("x", 2.0);
+
+    let root = ctx.parse("x + y * pi").unwrap();
+    assert!(root.evaluate_with_context(&ctx).is_ok_and(|x| x == 2.0));
+
+    ctx.set_strict_identifiers(true);
+    assert!(matches!(
+        ctx.parse("x + y * pi"),
+        Err(NodeError::UnknownVariable(name)) if name == "y"
+    ));
+    assert!(ctx.parse("x * pi").is_ok());
+}
+
+#[test]
+fn test_leaf_parser() {
+    let mut ctx = Context::new();
+    ctx.set_leaf_parser(Box::new(|literal| {
+        let (hours, minutes) = literal.split_once(':')?;
+        Some(hours.parse::<f64>().ok()? + minutes.parse::<f64>().ok()? / 60.0)
+    }));
+
+    let root = ctx.parse("1:30 * 2").unwrap();
+    assert!(root.evaluate().is_ok_and(|x| x == 3.0));
+    assert!(ctx
+        .parse("max(0:45, 1 - 0:30)")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 0.75));
+    assert!(ctx.parse("x + 1").is_ok());
+    assert!(Context::new().parse("1:30 * 2").is_err());
+}
+
+#[test]
+fn test_rounding_mode() {
+    assert_eq!(RoundingMode::HalfUp.round(3.5), 4.0);
+    assert_eq!(RoundingMode::HalfEven.round(3.5), 4.0);
+    assert_eq!(RoundingMode::HalfUp.round(2.5), 3.0);
+    assert_eq!(RoundingMode::HalfEven.round(2.5), 2.0);
+    assert_eq!(RoundingMode::HalfUp.round(-2.5), -3.0);
+    assert_eq!(RoundingMode::TowardZero.round(-2.7), -2.0);
+    assert_eq!(RoundingMode::AwayFromZero.round(-2.2), -3.0);
+    assert_eq!(RoundingMode::AwayFromZero.round(2.2), 3.0);
+
+    let mut ctx = Context::new();
+    let evaluate =
+        |ctx: &Context, input: &str| ctx.parse(input).unwrap().evaluate_with_context(ctx);
+    ctx.set_rounding_mode(Some(RoundingMode::HalfUp));
+    assert!(evaluate(&ctx, "7 / 2").is_ok_and(|x| x == 4.0));
+    assert!(evaluate(&ctx, "5 / 2").is_ok_and(|x| x == 3.0));
+    assert!(evaluate(&ctx, "5 / 2 * 2").is_ok_and(|x| x == 6.0));
+    ctx.set_rounding_mode(Some(RoundingMode::HalfEven));
+    assert!(evaluate(&ctx, "7 / 2").is_ok_and(|x| x == 4.0));
+    assert!(evaluate(&ctx, "5 / 2").is_ok_and(|x| x == 2.0));
+    ctx.set_rounding_mode(None);
+    assert!(evaluate(&ctx, "5 / 2").is_ok_and(|x| x == 2.5));
+}
@@ -0,0 +1,55 @@
+#[cfg(feature = "num-bigint")]
+use num_bigint::BigUint;
+#[cfg(feature = "num-bigint")]
+use std::sync::Mutex;
+
+pub fn fib_u128(n: usize) -> Option<u128> {
+    let (mut current, mut next) = (0u128, Some(1u128));
+    for _ in 0..n {
+        let following = next?;
+        (current, next) = (following, current.checked_add(following));
+    }
+    Some(current)
+}
+
+#[cfg(feature = "num-bigint")]
+pub fn fib(n: usize) -> BigUint {
+    if let Some(value) = fib_u128(n) {
+        return BigUint::from(value);
+    }
+
+    static CACHE: Mutex<Vec<BigUint>> = Mutex::new(Vec::new());
+    let mut cache = CACHE.lock().unwrap_or_else(|err| err.into_inner());
+    if cache.is_empty() {
+        cache.push(BigUint::from(0_u32));
+        cache.push(BigUint::from(1_u32));
+    }
+    while cache.len() <= n {
+        let next = &cache[cache.len() - 2] + &cache[cache.len() - 1];
+        cache.push(next);
+    }
+    cache[n].clone()
+}
+
+#[test]
+fn test_fib_u128() {
+    assert_eq!(fib_u128(0), Some(0));
+    assert_eq!(fib_u128(10), Some(55));
+    assert_eq!(fib_u128(100), Some(354224848179261915075));
+    assert_eq!(fib_u128(186), Some(332825110087067562321196029789634457848));
+    assert_eq!(fib_u128(187), None);
+}
+
+#[cfg(feature = "num-bigint")]
+#[test]
+fn test_fib() {
+    assert_eq!(fib(100).to_string(), "354224848179261915075");
+    assert_eq!(
+        fib(200).to_string(),
+        "280571172992510140037611932413038677189525"
+    );
+    assert_eq!(
+        fib(190).to_string(),
+        "2281217241465037496128651402858212007295"
+    );
+}
@@ -0,0 +1,122 @@
+use crate::node::NodeError;
+
+const MAX_EXACT_INTEGER: f64 = 9_007_199_254_740_992.0;
+
+pub fn format_result(value: f64) -> String {
+    if value.is_finite() && value.fract() == 0.0 && value.abs() <= MAX_EXACT_INTEGER {
+        format!("{}", value as i64)
+    } else if value.is_finite() && value.abs() > MAX_EXACT_INTEGER {
+        format!("{:e}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+pub fn to_fraction(value: f64, max_denominator: u64) -> (i64, i64) {
+    if value.is_nan() {
+        return (0, 0);
+    }
+    if value.is_infinite() || value.abs() > MAX_EXACT_INTEGER {
+        return (value.signum() as i64, 0);
+    }
+
+    let max_denominator = max_denominator.clamp(1, i64::MAX as u64) as i64;
+    let mut x = value;
+    let mut a = x.floor();
+    let (mut h_prev, mut h) = (1, a as i64);
+    let (mut k_prev, mut k) = (0, 1);
+    while x - a > 1e-12 {
+        x = 1.0 / (x - a);
+        a = x.floor();
+        let term = a as i64;
+        let next_k = term.checked_mul(k).and_then(|k| k.checked_add(k_prev));
+        let next_h = term.checked_mul(h).and_then(|h| h.checked_add(h_prev));
+        let (Some(next_h), Some(next_k)) = (next_h, next_k) else {
+            break;
+        };
+        if next_k > max_denominator {
+            break;
+        }
+        (h_prev, h) = (h, next_h);
+        (k_prev, k) = (k, next_k);
+    }
+    (h, k)
+}
+
+pub fn format_error(input: &str, err: &NodeError) -> String {
+    match err {
+        NodeError::UnexpectedToken { position, .. } => {
+            format!("{}\n{}\n{}^", err, input, " ".repeat(*position))
+        }
+        err => err.to_string(),
+    }
+}
+
+#[test]
+fn test_format_result() {
+    assert_eq!(format_result(3.0), "3");
+    assert_eq!(format_result(-0.0), "0");
+    assert_eq!(format_result(3.5), "3.5");
+    assert_eq!(format_result(1e20), "1e20");
+    assert_eq!(format_result(9_007_199_254_740_992.0), "9007199254740992");
+    assert_eq!(format_result(f64::INFINITY), "inf");
+}
+
+#[test]
+fn test_to_fraction() {
+    assert_eq!(to_fraction(0.75, 100), (3, 4));
+    assert_eq!(to_fraction(0.3333333, 100), (1, 3));
+    assert_eq!(to_fraction(0.5, 100), (1, 2));
+    assert_eq!(to_fraction(-1.25, 100), (-5, 4));
+    assert_eq!(to_fraction(3.0, 100), (3, 1));
+    assert_eq!(to_fraction(std::f64::consts::PI, 1000), (355, 113));
+    assert_eq!(to_fraction(std::f64::consts::PI, 100), (22, 7));
+    assert_eq!(to_fraction(0.1, 1), (0, 1));
+}
+
+#[test]
+fn test_format_error_after_rewritten_input() {
+    let input = "5² @";
+    let err = crate::parse(input).unwrap_err();
+    assert_eq!(
+        format_error(input, &err),
+        "Unexpected token '@' at position 3\n5² @\n   ^"
+    );
+
+    let input = "6 × 2 $";
+    let err = crate::parse(input).unwrap_err();
+    assert_eq!(
+        format_error(input, &err),
+        "Unexpected token '$' at position 6\n6 × 2 $\n      ^"
+    );
+}
+
+#[test]
+fn test_format_error() {
+    let input = "1 + @ 3";
+    let err = crate::parse(input).unwrap_err();
+    assert_eq!(
+        format_error(input, &err),
+        "Unexpected token '@' at position 4\n1 + @ 3\n    ^"
+    );
+
+    let input = "1 + ";
+    let err = crate::Parser::new()
+        .with_strict(true)
+        .parse(input)
+        .unwrap_err();
+    assert_eq!(
+        format_error(input, &err),
+        "Unexpected token '+' at position 2\n1 + \n  ^"
+    );
+
+    let input = "2 × (1 ÷ 0) # 3";
+    let err = crate::parse(input).unwrap_err();
+    assert!(format_error(input, &err).ends_with("\n2 × (1 ÷ 0) # 3\n            ^"));
+
+    let err = crate::evaluate_str("1 / 0").unwrap_err();
+    assert_eq!(
+        format_error("1 / 0", &err),
+        "Cannot divide by zero in '1 / 0'"
+    );
+}
@@ -0,0 +1,474 @@
+use crate::node::NodeError;
+use std::sync::OnceLock;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Arity {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+const FUNCTIONS: &[(&str, Arity)] = &[
+    ("gcd", Arity::Exactly(2)),
+    ("lcm", Arity::Exactly(2)),
+    ("sign", Arity::Exactly(1)),
+    ("signum", Arity::Exactly(1)),
+    ("copysign", Arity::Exactly(2)),
+    ("powmod", Arity::Exactly(3)),
+    ("fma", Arity::Exactly(3)),
+    ("tetrate", Arity::Exactly(2)),
+    ("hypot", Arity::Exactly(2)),
+    ("dist", Arity::Exactly(4)),
+    ("rem", Arity::Exactly(2)),
+    ("mod", Arity::Exactly(2)),
+    ("abs", Arity::Exactly(1)),
+    ("sqrt", Arity::Exactly(1)),
+    ("cbrt", Arity::Exactly(1)),
+    ("exp", Arity::Exactly(1)),
+    ("sin", Arity::Exactly(1)),
+    ("cos", Arity::Exactly(1)),
+    ("tan", Arity::Exactly(1)),
+    ("asin", Arity::Exactly(1)),
+    ("acos", Arity::Exactly(1)),
+    ("atan", Arity::Exactly(1)),
+    ("atan2", Arity::Exactly(2)),
+    ("deg", Arity::Exactly(1)),
+    ("rad", Arity::Exactly(1)),
+    ("sinh", Arity::Exactly(1)),
+    ("cosh", Arity::Exactly(1)),
+    ("tanh", Arity::Exactly(1)),
+    ("asinh", Arity::Exactly(1)),
+    ("acosh", Arity::Exactly(1)),
+    ("atanh", Arity::Exactly(1)),
+    ("ln", Arity::Exactly(1)),
+    ("log", Arity::Exactly(1)),
+    ("max", Arity::AtLeast(1)),
+    ("min", Arity::AtLeast(1)),
+    ("sum", Arity::AtLeast(1)),
+    ("avg", Arity::AtLeast(1)),
+    ("sum_range", Arity::Exactly(4)),
+];
+
+pub(crate) const RANGE_VARIABLE: &str = "i";
+const MAX_RANGE_VALUES: usize = 1_000_000;
+const MAX_TETRATION_STEPS: i64 = 1_000_000;
+
+pub fn supported_functions() -> &'static [&'static str] {
+    static NAMES: OnceLock<Vec<&str>> = OnceLock::new();
+    NAMES.get_or_init(|| FUNCTIONS.iter().map(|(name, _)| *name).collect())
+}
+
+pub(crate) fn is_function(name: &str) -> bool {
+    arity(name).is_some()
+}
+
+fn arity(name: &str) -> Option<Arity> {
+    FUNCTIONS
+        .iter()
+        .find(|(function, _)| *function == name)
+        .map(|(_, arity)| *arity)
+}
+
+pub(crate) fn check_arity(name: &str, argument_count: usize) -> Result<(), NodeError> {
+    match arity(name) {
+        None => Err(NodeError::InvalidExpression(format!(
+            "unknown function '{}'",
+            name
+        ))),
+        Some(Arity::Exactly(arity)) if arity != argument_count => {
+            Err(NodeError::InvalidExpression(format!(
+                "'{}' expects {} arguments, got {}",
+                name, arity, argument_count
+            )))
+        }
+        Some(Arity::AtLeast(arity)) if argument_count < arity => {
+            Err(NodeError::InvalidExpression(format!(
+                "'{}' expects at least {} arguments, got {}",
+                name, arity, argument_count
+            )))
+        }
+        Some(_) => Ok(()),
+    }
+}
+
+pub(crate) fn execute_function(name: &str, arguments: &[f64]) -> Result<f64, NodeError> {
+    check_arity(name, arguments.len())?;
+
+    match name {
+        "gcd" => Ok(gcd(
+            to_integer(name, arguments[0])?,
+            to_integer(name, arguments[1])?,
+        ) as f64),
+        "lcm" => lcm(
+            to_integer(name, arguments[0])?,
+            to_integer(name, arguments[1])?,
+        )
+        .map(|value| value as f64),
+        "rem" | "mod" if arguments[1] == 0.0 => Err(NodeError::DivideByZero {
+            expression: format!("{}({}, {})", name, arguments[0], arguments[1]),
+        }),
+        "rem" => Ok(arguments[0] % arguments[1]),
+        "mod" => Ok(arguments[0] - arguments[1] * (arguments[0] / arguments[1]).floor()),
+        "abs" => Ok(arguments[0].abs()),
+        "sqrt" if arguments[0] < 0.0 => Err(out_of_domain(name, "[0, inf)", arguments[0])),
+        "sqrt" => Ok(arguments[0].sqrt()),
+        "cbrt" => Ok(arguments[0].cbrt()),
+        "exp" => Ok(arguments[0].exp()),
+        "sin" => Ok(arguments[0].sin()),
+        "cos" => Ok(arguments[0].cos()),
+        "tan" => Ok(arguments[0].tan()),
+        "ln" | "log" if arguments[0] <= 0.0 => Err(out_of_domain(name, "(0, inf)", arguments[0])),
+        "asin" | "acos" if !(-1.0..=1.0).contains(&arguments[0]) => {
+            Err(out_of_domain(name, "[-1, 1]", arguments[0]))
+        }
+        "asin" => Ok(arguments[0].asin()),
+        "acos" => Ok(arguments[0].acos()),
+        "atan" => Ok(arguments[0].atan()),
+        "atan2" => Ok(arguments[0].atan2(arguments[1])),
+        "deg" => Ok(arguments[0].to_degrees()),
+        "rad" => Ok(arguments[0].to_radians()),
+        "sinh" => Ok(arguments[0].sinh()),
+        "cosh" => Ok(arguments[0].cosh()),
+        "tanh" => Ok(arguments[0].tanh()),
+        "asinh" => Ok(arguments[0].asinh()),
+        "acosh" if !(1.0..).contains(&arguments[0]) => {
+            Err(out_of_domain(name, "[1, inf)", arguments[0]))
+        }
+        "acosh" => Ok(arguments[0].acosh()),
+        "atanh" if arguments[0].is_nan() || arguments[0].abs() >= 1.0 => {
+            Err(out_of_domain(name, "(-1, 1)", arguments[0]))
+        }
+        "atanh" => Ok(arguments[0].atanh()),
+        "ln" => Ok(arguments[0].ln()),
+        "log" => Ok(arguments[0].log10()),
+        "max" => Ok(arguments.iter().copied().fold(f64::NEG_INFINITY, f64::max)),
+        "min" => Ok(arguments.iter().copied().fold(f64::INFINITY, f64::min)),
+        "sum" => Ok(arguments.iter().sum()),
+        "avg" => Ok(arguments.iter().sum::<f64>() / arguments.len() as f64),
+        "sum_range" => Err(NodeError::InvalidExpression(
+            "'sum_range' needs the evaluator to bind its loop variable".to_string(),
+        )),
+        "sign" | "signum" => Ok(sign(arguments[0])),
+        "copysign" => Ok(arguments[0].copysign(arguments[1])),
+        "fma" => Ok(arguments[0].mul_add(arguments[1], arguments[2])),
+        "hypot" => Ok(arguments[0].hypot(arguments[1])),
+        "dist" => Ok((arguments[2] - arguments[0]).hypot(arguments[3] - arguments[1])),
+        "tetrate" => tetrate(arguments[0], to_integer(name, arguments[1])?),
+        "powmod" => powmod(
+            to_integer(name, arguments[0])?,
+            to_integer(name, arguments[1])?,
+            to_integer(name, arguments[2])?,
+        )
+        .map(|value| value as f64),
+        _ => Err(NodeError::InvalidExpression(format!(
+            "unknown function '{}'",
+            name
+        ))),
+    }
+}
+
+pub(crate) fn range(start: f64, end: f64, step: f64) -> Result<Vec<f64>, NodeError> {
+    if step == 0.0 || !step.is_finite() || !start.is_finite() || !end.is_finite() {
+        return Err(NodeError::DomainError(format!(
+            "'sum_range' can't go from {} to {} by {}",
+            start, end, step
+        )));
+    }
+
+    let mut values = Vec::new();
+    let mut value = start;
+    while (step > 0.0 && value <= end) || (step < 0.0 && value >= end) {
+        if values.len() == MAX_RANGE_VALUES {
+            return Err(NodeError::DomainError(format!(
+                "'sum_range' is limited to {} values",
+                MAX_RANGE_VALUES
+            )));
+        }
+        values.push(value);
+        value = start + values.len() as f64 * step;
+    }
+    Ok(values)
+}
+
+fn out_of_domain(name: &str, domain: &str, value: f64) -> NodeError {
+    NodeError::DomainError(format!(
+        "'{}' expects an argument in {}, got {}",
+        name, domain, value
+    ))
+}
+
+fn sign(value: f64) -> f64 {
+    if value == 0.0 {
+        0.0
+    } else {
+        value.signum()
+    }
+}
+
+fn to_integer(function: &str, value: f64) -> Result<i64, NodeError> {
+    if value.fract() != 0.0 || value.abs() >= i64::MAX as f64 {
+        return Err(NodeError::DomainError(format!(
+            "'{}' expects integer arguments, got {}",
+            function, value
+        )));
+    }
+
+    Ok(value as i64)
+}
+
+fn gcd(a: i64, b: i64) -> u64 {
+    let (mut a, mut b) = (a.unsigned_abs(), b.unsigned_abs());
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+fn lcm(a: i64, b: i64) -> Result<u64, NodeError> {
+    if a == 0 || b == 0 {
+        return Ok(0);
+    }
+
+    (a.unsigned_abs() / gcd(a, b))
+        .checked_mul(b.unsigned_abs())
+        .ok_or(NodeError::Overflow)
+}
+
+fn powmod(base: i64, exponent: i64, modulus: i64) -> Result<i64, NodeError> {
+    if modulus <= 0 {
+        return Err(NodeError::DomainError(format!(
+            "'powmod' expects a positive modulus, got {}",
+            modulus
+        )));
+    }
+    if exponent < 0 {
+        return Err(NodeError::DomainError(format!(
+            "'powmod' expects a non-negative exponent, got {}",
+            exponent
+        )));
+    }
+
+    let modulus = modulus as i128;
+    let mut base = (base as i128).rem_euclid(modulus);
+    let mut exponent = exponent;
+    let mut result = 1 % modulus;
+    while exponent > 0 {
+        if exponent % 2 == 1 {
+            result = result * base % modulus;
+        }
+        base = base * base % modulus;
+        exponent /= 2;
+    }
+
+    Ok(result as i64)
+}
+
+fn tetrate(base: f64, height: i64) -> Result<f64, NodeError> {
+    if height < 0 {
+        return Err(out_of_domain("tetrate", "[0, inf)", height as f64));
+    }
+
+    let (mut previous, mut result) = (f64::NAN, 1.0);
+    for step in 0..height.min(MAX_TETRATION_STEPS) {
+        let next = base.powf(result);
+        if next.is_infinite() {
+            return Err(NodeError::Overflow);
+        }
+        if next == result {
+            return Ok(result);
+        }
+        if next == previous {
+            let remaining = height - step - 1;
+            return Ok(if remaining % 2 == 0 { next } else { result });
+        }
+        (previous, result) = (result, next);
+    }
+
+    if height > MAX_TETRATION_STEPS {
+        return Err(NodeError::DomainError(format!(
+            "'tetrate' doesn't settle within {} steps for base {}",
+            MAX_TETRATION_STEPS, base
+        )));
+    }
+    Ok(result)
+}
+
+#[test]
+fn test_sign() {
+    assert!(crate::evaluate_str("sign -5").is_ok_and(|x| x == -1.0));
+    assert!(crate::evaluate_str("sign 0").is_ok_and(|x| x == 0.0));
+    assert!(crate::evaluate_str("sign 3").is_ok_and(|x| x == 1.0));
+    assert!(crate::evaluate_str("signum(-0.5)").is_ok_and(|x| x == -1.0));
+}
+
+#[test]
+fn test_copysign() {
+    assert!(crate::evaluate_str("copysign(3, -1)").is_ok_and(|x| x == -3.0));
+    assert!(crate::evaluate_str("copysign(-2, 4)").is_ok_and(|x| x == 2.0));
+}
+
+#[test]
+fn test_powmod() {
+    assert!(crate::evaluate_str("powmod(2, 10, 1000)").is_ok_and(|x| x == 24.0));
+    assert!(crate::evaluate_str("powmod(3, 4, 5)").is_ok_and(|x| x == 1.0));
+    assert!(crate::evaluate_str("powmod(2, 10, 0)").is_err());
+    assert!(crate::evaluate_str("powmod(2.5, 10, 7)").is_err());
+}
+
+#[test]
+fn test_remainder_and_modulo() {
+    assert!(crate::evaluate_str("rem(-7, 3)").is_ok_and(|x| x == -1.0));
+    assert!(crate::evaluate_str("mod(-7, 3)").is_ok_and(|x| x == 2.0));
+    assert!(crate::evaluate_str("rem(7, -3)").is_ok_and(|x| x == 1.0));
+    assert!(crate::evaluate_str("mod(7, -3)").is_ok_and(|x| x == -2.0));
+    assert!(crate::evaluate_str("mod(5.5, 2)").is_ok_and(|x| x == 1.5));
+    assert!(matches!(
+        crate::evaluate_str("mod(1, 0)"),
+        Err(NodeError::DivideByZero { .. })
+    ));
+}
+
+#[test]
+fn test_fma() {
+    assert!(crate::evaluate_str("fma(2, 3, 4)").is_ok_and(|x| x == 10.0));
+    assert!(crate::evaluate_str("0.1 * 10 - 1").is_ok_and(|x| x == 0.0));
+    assert!(crate::evaluate_str("fma(0.1, 10, -1)")
+        .is_ok_and(|x| x != 0.0 && x == 0.1f64.mul_add(10.0, -1.0)));
+}
+
+#[test]
+fn test_tetrate() {
+    assert!(crate::evaluate_str("tetrate(2, 3)").is_ok_and(|x| x == 16.0));
+    assert!(crate::evaluate_str("tetrate(2, 4)").is_ok_and(|x| x == 65536.0));
+    assert!(crate::evaluate_str("tetrate(5, 0)").is_ok_and(|x| x == 1.0));
+    assert!(crate::evaluate_str("tetrate(1, 1000000000000)").is_ok_and(|x| x == 1.0));
+    assert!(matches!(
+        crate::evaluate_str("tetrate(10, 10)"),
+        Err(NodeError::Overflow)
+    ));
+    assert!(crate::evaluate_str("tetrate(2, -1)").is_err());
+
+    let tower = |height: i64| (0..height).fold(1.0, |result: f64, _| 0.05f64.powf(result));
+    assert!(crate::evaluate_str("tetrate(0.05, 1000000000000)").is_ok_and(|x| x == tower(1000)));
+    assert!(crate::evaluate_str("tetrate(0.05, 1000000000001)").is_ok_and(|x| x == tower(1001)));
+    assert!(tower(1000) != tower(1001));
+}
+
+#[test]
+fn test_hypot_and_dist() {
+    assert!(crate::evaluate_str("hypot(3, 4)").is_ok_and(|x| x == 5.0));
+    assert!(crate::evaluate_str("hypot(1e200, 1e200)").is_ok_and(|x| x.is_finite()));
+    assert!(crate::evaluate_str("dist(0, 0, 3, 4)").is_ok_and(|x| x == 5.0));
+    assert!(crate::evaluate_str("dist(1, -1, -2, 3)").is_ok_and(|x| x == 5.0));
+    assert!(crate::evaluate_str("dist(0, 0, 3)").is_err());
+}
+
+#[test]
+fn test_nested_functions() {
+    assert!(crate::evaluate_str("sqrt(abs(-16))").is_ok_and(|x| x == 4.0));
+    assert!(crate::evaluate_str("sqrt(sin(0) + 1)").is_ok_and(|x| x == 1.0));
+    assert!(
+        crate::evaluate_str("sin(cos(0))").is_ok_and(|x| crate::approx_eq(x, 1f64.sin(), 1e-12))
+    );
+    assert!(crate::evaluate_str("2 * sqrt(gcd(8, 12) * 4) + 1").is_ok_and(|x| x == 9.0));
+}
+
+#[test]
+fn test_exp_and_cbrt() {
+    assert!(crate::evaluate_str("exp 0").is_ok_and(|x| x == 1.0));
+    assert!(crate::evaluate_str("exp 1").is_ok_and(|x| crate::approx_eq(
+        x,
+        std::f64::consts::E,
+        1e-12
+    )));
+    assert!(crate::evaluate_str("cbrt -8").is_ok_and(|x| x == -2.0));
+    assert!(
+        crate::evaluate_str("cbrt(27) + exp(ln 2)").is_ok_and(|x| crate::approx_eq(x, 5.0, 1e-12))
+    );
+}
+
+#[test]
+fn test_trig_and_log() {
+    assert!(crate::evaluate_str("tan 1").is_ok_and(|x| crate::approx_eq(
+        x,
+        1.5574077246549023,
+        1e-12
+    )));
+    assert!(
+        crate::evaluate_str("ln(log(1000))").is_ok_and(|x| crate::approx_eq(x, 3f64.ln(), 1e-12))
+    );
+    assert!(crate::evaluate_str("ln 0").is_err());
+    assert!(crate::evaluate_str("sqrt -1").is_err());
+}
+
+#[test]
+fn test_supported_functions_are_dispatched() {
+    assert_eq!(supported_functions().len(), FUNCTIONS.len());
+    for (name, arity) in FUNCTIONS {
+        let (Arity::Exactly(count) | Arity::AtLeast(count)) = *arity;
+        let result = execute_function(name, &vec![1.0; count]);
+        assert!(
+            !matches!(&result, Err(NodeError::InvalidExpression(message))
+                if message.starts_with("unknown function")),
+            "'{}' is not dispatched",
+            name
+        );
+    }
+}
+
+#[test]
+fn test_variadic_functions() {
+    assert!(crate::evaluate_str("max(3, 7, 1, 9)").is_ok_and(|x| x == 9.0));
+    assert!(crate::evaluate_str("min(3, 7, -1, 9)").is_ok_and(|x| x == -1.0));
+    assert!(crate::evaluate_str("sum(1, 2, 3)").is_ok_and(|x| x == 6.0));
+    assert!(crate::evaluate_str("avg(2, 4)").is_ok_and(|x| x == 3.0));
+    assert!(crate::evaluate_str("max(5)").is_ok_and(|x| x == 5.0));
+    assert!(crate::evaluate_str("sum()").is_err());
+}
+
+#[test]
+fn test_hyperbolic_functions() {
+    assert!(crate::evaluate_str("sinh 0").is_ok_and(|x| x == 0.0));
+    assert!(crate::evaluate_str("cosh 0").is_ok_and(|x| x == 1.0));
+    assert!(crate::evaluate_str("tanh(asinh(0))").is_ok_and(|x| x == 0.0));
+    assert!(crate::evaluate_str("acosh(cosh(2))").is_ok_and(|x| crate::approx_eq(x, 2.0, 1e-12)));
+    assert!(
+        crate::evaluate_str("atanh 0.5").is_ok_and(|x| crate::approx_eq(
+            x,
+            0.5493061443340549,
+            1e-12
+        ))
+    );
+    assert!(crate::evaluate_str("atanh 2").is_err());
+    assert!(crate::evaluate_str("acosh 0.5").is_err());
+}
+
+#[test]
+fn test_inverse_trigonometric_functions() {
+    let half_pi = std::f64::consts::FRAC_PI_2;
+    assert!(crate::evaluate_str("asin 1").is_ok_and(|x| crate::approx_eq(x, half_pi, 1e-12)));
+    assert!(crate::evaluate_str("acos 1").is_ok_and(|x| x == 0.0));
+    assert!(
+        crate::evaluate_str("atan(1) * 4").is_ok_and(|x| crate::approx_eq(
+            x,
+            std::f64::consts::PI,
+            1e-12
+        ))
+    );
+    assert!(crate::evaluate_str("atan2(1, 0)").is_ok_and(|x| crate::approx_eq(x, half_pi, 1e-12)));
+    assert!(crate::evaluate_str("asin 2").is_err());
+    assert!(crate::evaluate_str("acos(-1.5)").is_err());
+}
+
+#[test]
+fn test_angle_conversions() {
+    assert!(crate::evaluate_str("deg(pi)").is_ok_and(|x| x == 180.0));
+    assert!(
+        crate::evaluate_str("rad(180)").is_ok_and(|x| crate::approx_eq(
+            x,
+            std::f64::consts::PI,
+            1e-12
+        ))
+    );
+    assert!(crate::evaluate_str("sin(rad(90))").is_ok_and(|x| crate::approx_eq(x, 1.0, 1e-12)));
+    assert!(crate::evaluate_str("deg(atan(1))").is_ok_and(|x| crate::approx_eq(x, 45.0, 1e-12)));
+}
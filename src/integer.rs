@@ -0,0 +1,156 @@
+use crate::node::{Node, NodeError, NodeValue};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    Wrap,
+    Saturate,
+    Error,
+}
+
+struct Width {
+    bits: u32,
+    policy: OverflowPolicy,
+}
+
+impl Width {
+    fn reduce(&self, value: i128) -> Result<i64, NodeError> {
+        let min = -(1i128 << (self.bits - 1));
+        let max = (1i128 << (self.bits - 1)) - 1;
+
+        match self.policy {
+            _ if (min..=max).contains(&value) => Ok(value as i64),
+            OverflowPolicy::Wrap => {
+                let modulus = 1i128 << self.bits;
+                let value = value.rem_euclid(modulus);
+                Ok(if value > max { value - modulus } else { value } as i64)
+            }
+            OverflowPolicy::Saturate => Ok(value.clamp(min, max) as i64),
+            OverflowPolicy::Error => Err(NodeError::Overflow),
+        }
+    }
+
+    fn pow(&self, base: i64, exponent: i64) -> Result<i64, NodeError> {
+        if exponent < 0 {
+            return Err(NodeError::DomainError(format!(
+                "integer exponents must be non-negative, got {}",
+                exponent
+            )));
+        }
+
+        let (mut result, mut base, mut exponent) = (self.reduce(1)?, base, exponent);
+        while exponent > 0 {
+            if exponent % 2 == 1 {
+                result = self.reduce(result as i128 * base as i128)?;
+            }
+            exponent /= 2;
+            if exponent > 0 {
+                base = self.reduce(base as i128 * base as i128)?;
+            }
+        }
+        Ok(result)
+    }
+}
+
+impl Node {
+    pub fn evaluate_int_wrapping(
+        &self,
+        bits: u32,
+        policy: OverflowPolicy,
+    ) -> Result<i64, NodeError> {
+        if !(1..=64).contains(&bits) {
+            return Err(NodeError::DomainError(format!(
+                "integer widths must be between 1 and 64 bits, got {}",
+                bits
+            )));
+        }
+
+        self.evaluate_int_in(&Width { bits, policy })
+    }
+
+    fn evaluate_int_in(&self, width: &Width) -> Result<i64, NodeError> {
+        if !self.has_children() {
+            return match self.value {
+                NodeValue::Number(number)
+                    if number.fract() == 0.0 && number.abs() < 2f64.powi(127) =>
+                {
+                    width.reduce(number as i128)
+                }
+                _ => Err(NodeError::InvalidExpression(format!(
+                    "'{}' is not an integer",
+                    self.value
+                ))),
+            };
+        }
+
+        let l_operand = match &self.l_child {
+            None => 0,
+            Some(l_child) => l_child.evaluate_int_in(width)?,
+        };
+        let r_operand = match &self.r_child {
+            None => 0,
+            Some(r_child) => r_child.evaluate_int_in(width)?,
+        };
+
+        let (a, b) = (l_operand as i128, r_operand as i128);
+        match self.operator() {
+            "+" => width.reduce(a + b),
+            "-" => width.reduce(a - b),
+            "*" => width.reduce(a * b),
+            "/" if b == 0 => Err(NodeError::DivideByZero {
+                expression: self.to_infix(),
+            }),
+            "/" => width.reduce(a / b),
+            "^" => width.pow(l_operand, r_operand),
+            operator => Err(NodeError::InvalidExpression(format!(
+                "'{}' is not supported in integer evaluation",
+                operator
+            ))),
+        }
+    }
+}
+
+#[test]
+fn test_wrapping_and_saturating() {
+    let root = Node::from_expression("200 + 100".to_string());
+    assert_eq!(
+        root.evaluate_int_wrapping(8, OverflowPolicy::Wrap).unwrap(),
+        44
+    );
+    assert_eq!(
+        root.evaluate_int_wrapping(8, OverflowPolicy::Saturate)
+            .unwrap(),
+        127
+    );
+    assert!(matches!(
+        root.evaluate_int_wrapping(8, OverflowPolicy::Error),
+        Err(NodeError::Overflow)
+    ));
+    assert_eq!(
+        root.evaluate_int_wrapping(32, OverflowPolicy::Error)
+            .unwrap(),
+        300
+    );
+
+    let root = Node::from_expression("2 ^ 31".to_string());
+    assert_eq!(
+        root.evaluate_int_wrapping(32, OverflowPolicy::Wrap)
+            .unwrap(),
+        i32::MIN as i64
+    );
+    assert_eq!(
+        root.evaluate_int_wrapping(32, OverflowPolicy::Saturate)
+            .unwrap(),
+        i32::MAX as i64
+    );
+
+    let root = Node::from_expression("(-7 / 2) * 3".to_string());
+    assert_eq!(
+        root.evaluate_int_wrapping(64, OverflowPolicy::Error)
+            .unwrap(),
+        -9
+    );
+    assert!(Node::from_expression("1.5 + 1".to_string())
+        .evaluate_int_wrapping(16, OverflowPolicy::Wrap)
+        .is_err());
+    assert!(root.evaluate_int_wrapping(0, OverflowPolicy::Wrap).is_err());
+}
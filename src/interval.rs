@@ -0,0 +1,111 @@
+use crate::node::{constant, unquote, Node, NodeError, NodeValue};
+use std::collections::HashMap;
+
+impl Node {
+    pub fn evaluate_interval(
+        &self,
+        ranges: &HashMap<String, (f64, f64)>,
+    ) -> Result<(f64, f64), NodeError> {
+        if !self.has_children() {
+            return match &self.value {
+                NodeValue::Number(number) => Ok((*number, *number)),
+                NodeValue::Variable(name) => {
+                    let name = unquote(name);
+                    match (constant(name), ranges.get(name)) {
+                        (Some(value), _) => Ok((value, value)),
+                        (None, Some((low, high))) => Ok((low.min(*high), low.max(*high))),
+                        (None, None) => Err(NodeError::UnknownVariable(name.to_string())),
+                    }
+                }
+                NodeValue::Operator(operator) => {
+                    Err(NodeError::InvalidExpression(operator.to_string()))
+                }
+            };
+        }
+
+        let (r_low, r_high) = match &self.r_child {
+            None => (0.0, 0.0),
+            Some(r_child) => r_child.evaluate_interval(ranges)?,
+        };
+        let (l_low, l_high) = match &self.l_child {
+            None => (0.0, 0.0),
+            Some(l_child) => l_child.evaluate_interval(ranges)?,
+        };
+
+        match self.operator() {
+            "+" => Ok((l_low + r_low, l_high + r_high)),
+            "-" => Ok((l_low - r_high, l_high - r_low)),
+            "*" => Ok(bounds(&[
+                l_low * r_low,
+                l_low * r_high,
+                l_high * r_low,
+                l_high * r_high,
+            ])),
+            "/" if r_low <= 0.0 && r_high >= 0.0 => Err(NodeError::DivideByZero {
+                expression: self.to_infix(),
+            }),
+            "/" => Ok(bounds(&[
+                l_low / r_low,
+                l_low / r_high,
+                l_high / r_low,
+                l_high / r_high,
+            ])),
+            "^" if r_low == r_high && r_low >= 0.0 && r_low.fract() == 0.0 => {
+                let (low, high) = (l_low.powf(r_low), l_high.powf(r_low));
+                if r_low % 2.0 == 0.0 && l_low < 0.0 && l_high > 0.0 {
+                    Ok((0.0, low.max(high)))
+                } else {
+                    Ok((low.min(high), low.max(high)))
+                }
+            }
+            operator => Err(NodeError::InvalidExpression(format!(
+                "'{}' is not supported in interval evaluation",
+                operator
+            ))),
+        }
+    }
+}
+
+fn bounds(values: &[f64]) -> (f64, f64) {
+    values
+        .iter()
+        .fold((f64::INFINITY, f64::NEG_INFINITY), |(low, high), value| {
+            (low.min(*value), high.max(*value))
+        })
+}
+
+#[test]
+fn test_interval_addition() {
+    let ranges = HashMap::from([("x".to_string(), (0.0, 2.0))]);
+    let root = Node::from_expression("x + 1".to_string());
+    assert_eq!(root.evaluate_interval(&ranges).unwrap(), (1.0, 3.0));
+}
+
+#[test]
+fn test_interval_multiplication_and_division() {
+    let ranges = HashMap::from([
+        ("x".to_string(), (-1.0, 2.0)),
+        ("y".to_string(), (2.0, 4.0)),
+    ]);
+    let root = Node::from_expression("x * x".to_string());
+    assert_eq!(root.evaluate_interval(&ranges).unwrap(), (-2.0, 4.0));
+
+    let root = Node::from_expression("x ^ 2".to_string());
+    assert_eq!(root.evaluate_interval(&ranges).unwrap(), (0.0, 4.0));
+
+    let root = Node::from_expression("8 / y - x".to_string());
+    assert_eq!(root.evaluate_interval(&ranges).unwrap(), (0.0, 5.0));
+
+    let root = Node::from_expression("1 / x".to_string());
+    assert!(matches!(
+        root.evaluate_interval(&ranges),
+        Err(NodeError::DivideByZero { .. })
+    ));
+}
+
+#[test]
+fn test_interval_quoted_variable() {
+    let ranges = HashMap::from([("total cost".to_string(), (1.0, 3.0))]);
+    let root = crate::node::parse("\"total cost\" * 2").unwrap();
+    assert_eq!(root.evaluate_interval(&ranges).unwrap(), (2.0, 6.0));
+}
@@ -0,0 +1,47 @@
+mod algebra;
+pub mod batch;
+#[cfg(feature = "num-bigint")]
+mod bigint;
+#[cfg(feature = "num-complex")]
+mod complex;
+pub mod context;
+pub mod fib;
+pub mod format;
+pub mod functions;
+pub mod integer;
+mod interval;
+pub mod lint;
+pub mod node;
+pub mod parser;
+#[cfg(feature = "num-rational")]
+mod rational;
+pub mod reduce;
+pub mod repl;
+pub mod sampling;
+pub mod token;
+pub mod units;
+
+pub use algebra::solve_linear;
+#[cfg(feature = "num-complex")]
+pub use complex::to_polar;
+pub use context::{Context, RoundingMode};
+pub use format::{format_error, format_result, to_fraction};
+pub use functions::supported_functions;
+pub use integer::OverflowPolicy;
+pub use lint::redundant_parens;
+pub use node::{
+    apply_template, approx_eq, evaluate_str, operator_info, parse, precedence, tree_diff,
+    Associativity, Node, NodeError, NodeValue, OperatorInfo, SUPPORTED_OPERATORS,
+};
+#[cfg(feature = "num-bigint")]
+pub use num_bigint::{BigInt, BigUint};
+#[cfg(feature = "num-complex")]
+pub use num_complex::Complex;
+#[cfg(feature = "num-rational")]
+pub use num_rational::Rational64;
+pub use parser::{is_complete, is_valid, parse_collecting, Parser};
+pub use reduce::{reduce_steps, Reduced};
+pub use repl::{run_repl, Repl};
+pub use sampling::{probably_equal, sample};
+pub use token::{tokenize, Token, TokenKind};
+pub use units::Unit;
@@ -0,0 +1,58 @@
+use crate::functions;
+use crate::node::Node;
+use crate::token::{tokenize, Token, TokenKind};
+use std::collections::HashSet;
+
+pub fn redundant_parens(input: &str) -> Vec<(usize, usize)> {
+    let tokens = tokenize(input);
+    let root = Node::from_tokens(&tokens);
+
+    let mut pairs = Vec::new();
+    let mut open = Vec::new();
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => open.push(index),
+            TokenKind::RightParen => {
+                if let Some(start) = open.pop() {
+                    pairs.push((start, index));
+                }
+            }
+            _ => {}
+        }
+    }
+    pairs.sort();
+
+    let mut removed = HashSet::new();
+    let mut redundant = Vec::new();
+    for (start, end) in pairs {
+        let is_call = start > 0
+            && tokens[start - 1].kind == TokenKind::Identifier
+            && functions::is_function(tokens[start - 1].text);
+        if is_call {
+            continue;
+        }
+
+        let without_pair = (0..tokens.len())
+            .filter(|index| !removed.contains(index) && *index != start && *index != end)
+            .map(|index| tokens[index])
+            .collect::<Vec<Token>>();
+        if Node::from_tokens(&without_pair) == root {
+            redundant.push((tokens[start].start, tokens[end].end));
+            removed.insert(start);
+            removed.insert(end);
+        }
+    }
+
+    redundant
+}
+
+#[test]
+fn test_redundant_parens() {
+    assert_eq!(redundant_parens("((1 + 2))"), vec![(0, 9), (1, 8)]);
+    assert_eq!(redundant_parens("(1 + 2)"), vec![(0, 7)]);
+    assert_eq!(redundant_parens("((x))"), vec![(0, 5), (1, 4)]);
+    assert_eq!(redundant_parens("(1 + 2) * 3"), vec![]);
+    assert_eq!(redundant_parens("3 + (2 * x)"), vec![(4, 11)]);
+    assert_eq!(redundant_parens("sqrt((4))"), vec![(5, 8)]);
+    assert_eq!(redundant_parens("gcd(a, b) * (1 - 2)"), vec![]);
+}
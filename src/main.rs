@@ -1,62 +1,43 @@
-use crate::node::Node;
-use std::io;
-
-mod node;
+use calculator_v2::batch;
+use calculator_v2::repl::{bindings_from_env, Repl};
+use std::io::{self, IsTerminal};
+use std::process;
 
 fn main() {
-    loop {
-        println!("Input the expression to be parsed or 'end' to exit");
-
-        let input = read();
-
-        if input == "end" {
-            return;
-        }
-
-        if &input[0..3] == "fib" {
-            let argument = input[3..].trim().parse::<usize>().unwrap_or(1);
-            println!("fib({}) = {}", argument, fib(argument));
-            continue;
-        }
-
-        let root = Node::from_expression(input);
-
-        match root.evaluate() {
-            Ok(result) => {
-                println!("The tree representing the operation:\n{}", root);
-
-                println!("The entered expression evaluates to: {}", result)
-            }
-            Err(err) => println!("Error: {}", err),
-        }
+    let args = std::env::args().skip(1).collect::<Vec<String>>();
+    let tree_only = args.iter().any(|arg| arg == "--tree-only" || arg == "-t");
+    let environment = std::env::vars_os()
+        .filter_map(|(key, value)| Some((key.into_string().ok()?, value.into_string().ok()?)));
+    let color = args.iter().any(|arg| arg == "--color")
+        || (io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none());
+
+    if let Some(path) = args.iter().find(|arg| !arg.starts_with('-')) {
+        run_file(path);
+        return;
     }
-}
 
-fn read() -> String {
-    let mut input = String::new();
-    io::stdin()
-        .read_line(&mut input)
-        .expect("Failed to read line");
-    return input.trim().to_string();
+    Repl::new("Input the expression to be parsed or 'end' to exit")
+        .tree_only(tree_only)
+        .color(color)
+        .with_variables(bindings_from_env(environment))
+        .run(io::stdin().lock(), io::stdout())
+        .expect("Failed to run the prompt");
 }
 
-fn fib(n: usize) -> u128 {
-    let mut cache: Vec<u128> = [1, 1].to_vec();
-
-    rec_fib(n, &mut cache)
-}
-
-fn rec_fib(n: usize, cache: &mut Vec<u128>) -> u128 {
-    match n {
-        number => {
-            if cache.len() >= number {
-                cache[number - 1]
-            } else {
-                let fib_value = rec_fib(n - 2, cache) + rec_fib(n - 1, cache);
-                cache.insert(number - 1, fib_value);
-
-                cache[number - 1]
+fn run_file(path: &str) {
+    match batch::evaluate_file(path) {
+        Ok(results) => {
+            for result in results {
+                println!("{}", result);
             }
         }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            eprintln!("File not found: {}", path);
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("Could not read {}: {}", path, err);
+            process::exit(1);
+        }
     }
 }
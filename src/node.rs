@@ -1,17 +1,62 @@
+use crate::context::{Context, RoundingMode};
+use crate::functions;
+use crate::parser::Parser;
+use crate::token::{tokenize, Token, TokenKind};
+use std::collections::HashMap;
 use std::error::Error;
+use std::hash::{Hash, Hasher};
 
-const OPERATORS: &str = "+-*/^";
+pub(crate) const CONSTANTS: &[(&str, f64)] = &[
+    ("pi", std::f64::consts::PI),
+    ("e", std::f64::consts::E),
+    ("tau", std::f64::consts::TAU),
+];
 
+pub const SUPPORTED_OPERATORS: &[&str] = &[
+    "+", "-", "*", "/", "^", "<", ">", "<=", ">=", "==", "!=", "&&",
+];
+
+#[derive(Clone, Debug)]
+pub enum NodeValue {
+    Number(f64),
+    Operator(String),
+    Variable(String),
+}
+
+#[derive(Clone, Debug)]
 pub struct Node {
-    pub value: String,
+    pub value: NodeValue,
     pub l_child: Option<Box<Node>>,
     pub r_child: Option<Box<Node>>,
+    pub span: Option<(usize, usize)>,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value && self.l_child == other.l_child && self.r_child == other.r_child
+    }
+}
+
+impl Eq for Node {}
+
+impl Hash for Node {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state);
+        self.l_child.hash(state);
+        self.r_child.hash(state);
+    }
 }
 
 #[derive(Debug)]
 pub enum NodeError {
     InvalidExpression(String),
-    DivideByZero,
+    DivideByZero { expression: String },
+    DomainError(String),
+    Overflow,
+    StepLimitExceeded,
+    UnknownVariable(String),
+    IncompatibleUnits(String),
+    UnexpectedToken { token: String, position: usize },
 }
 
 impl Error for NodeError {}
@@ -24,96 +69,553 @@ impl std::fmt::Display for NodeError {
             match self {
                 NodeError::InvalidExpression(msg) =>
                     format!("The entered expression is invalid: {}", msg),
-                NodeError::DivideByZero => "Cannot divide by zero".to_string(),
+                NodeError::DivideByZero { expression } =>
+                    format!("Cannot divide by zero in '{}'", expression),
+                NodeError::DomainError(msg) => format!("Argument out of domain: {}", msg),
+                NodeError::Overflow => "The result is too large to represent".to_string(),
+                NodeError::StepLimitExceeded =>
+                    "The evaluation exceeded the step limit".to_string(),
+                NodeError::UnknownVariable(name) => format!("Unknown variable: {}", name),
+                NodeError::IncompatibleUnits(msg) => format!("Incompatible units: {}", msg),
+                NodeError::UnexpectedToken { token, position } =>
+                    format!("Unexpected token '{}' at position {}", token, position),
             }
         )
     }
 }
 
+impl NodeValue {
+    fn canonical_bits(number: f64) -> u64 {
+        if number.is_nan() {
+            f64::NAN.to_bits()
+        } else {
+            (number + 0.0).to_bits()
+        }
+    }
+}
+
+impl PartialEq for NodeValue {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (NodeValue::Number(a), NodeValue::Number(b)) => {
+                Self::canonical_bits(*a) == Self::canonical_bits(*b)
+            }
+            (NodeValue::Operator(a), NodeValue::Operator(b)) => a == b,
+            (NodeValue::Variable(a), NodeValue::Variable(b)) => a == b,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for NodeValue {}
+
+impl Hash for NodeValue {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            NodeValue::Number(number) => Self::canonical_bits(*number).hash(state),
+            NodeValue::Operator(name) | NodeValue::Variable(name) => name.hash(state),
+        }
+    }
+}
+
+impl std::fmt::Display for NodeValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            NodeValue::Number(number) => write!(f, "{}", number),
+            NodeValue::Operator(name) | NodeValue::Variable(name) => write!(f, "{}", name),
+        }
+    }
+}
+
 impl std::fmt::Display for Node {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{}", self.to_string())
+        write!(f, "{}", self.to_string_max_depth(usize::MAX))
     }
 }
 
 impl Node {
     pub fn from_expression(expression: String) -> Self {
-        let (operator, l_expression, r_expression) = split_on_lowest_priority_operator(expression);
+        let normalized = Parser::default().normalize(&expression);
+        let mut root = Self::from_tokens(&tokenize(&normalized.text));
+        root.map_spans(&|span| normalized.source_span(span));
+        root
+    }
 
-        let l_child: Option<Box<Node>>;
-        let r_child: Option<Box<Node>>;
-        if l_expression == "".to_string() {
-            l_child = None;
-        } else {
-            l_child = Some(Box::new(Self::from_expression(l_expression)));
+    pub fn leaf(value: &str) -> Self {
+        Node {
+            value: match parse_number(value) {
+                Some(number) => NodeValue::Number(number),
+                None => NodeValue::Variable(value.to_string()),
+            },
+            l_child: None,
+            r_child: None,
+            span: None,
         }
-        if r_expression == "".to_string() {
-            r_child = None;
-        } else {
-            r_child = Some(Box::new(Self::from_expression(r_expression)));
+    }
+
+    pub fn number(value: f64) -> Self {
+        Node {
+            value: NodeValue::Number(value),
+            l_child: None,
+            r_child: None,
+            span: None,
         }
+    }
 
+    pub fn binary(operator: &str, left: Node, right: Node) -> Self {
         Node {
-            value: operator,
-            l_child: l_child,
-            r_child: r_child,
+            value: NodeValue::Operator(operator.to_string()),
+            l_child: Some(Box::new(left)),
+            r_child: Some(Box::new(right)),
+            span: None,
+        }
+    }
+
+    pub(crate) fn from_tokens(tokens: &[Token]) -> Self {
+        Self::from_tokens_in(tokens, false)
+    }
+
+    pub(crate) fn from_tokens_in(tokens: &[Token], left_to_right: bool) -> Self {
+        if is_wrapped_in_parentheses(tokens) {
+            return Self::from_tokens_in(&tokens[1..tokens.len() - 1], left_to_right);
+        }
+
+        let child = |tokens: &[Token]| {
+            if tokens.is_empty() {
+                None
+            } else {
+                Some(Box::new(Self::from_tokens_in(tokens, left_to_right)))
+            }
+        };
+
+        if let Some(root) = Self::from_let_tokens(tokens, left_to_right) {
+            return root;
+        }
+
+        match split_on_lowest_priority_operator(tokens, left_to_right) {
+            Some(index) if is_comparison(tokens[index].value()) && !left_to_right => {
+                Self::from_comparison_chain(tokens)
+            }
+            Some(index) => Node {
+                value: NodeValue::Operator(tokens[index].value().to_string()),
+                l_child: child(&tokens[..index]),
+                r_child: child(&tokens[index + 1..]),
+                span: Some((tokens[index].start, tokens[index].end)),
+            },
+            None if tokens.len() > 1 && functions::is_function(tokens[0].text) => Node {
+                value: NodeValue::Operator(tokens[0].text.to_string()),
+                l_child: None,
+                r_child: child(&tokens[1..]),
+                span: Some((tokens[0].start, tokens[0].end)),
+            },
+            None => Node {
+                span: tokens
+                    .first()
+                    .zip(tokens.last())
+                    .map(|(first, last)| (first.start, last.end)),
+                ..Self::leaf(
+                    &tokens
+                        .iter()
+                        .map(|token| token.text)
+                        .collect::<Vec<&str>>()
+                        .join(" "),
+                )
+            },
+        }
+    }
+
+    fn from_let_tokens(tokens: &[Token], left_to_right: bool) -> Option<Self> {
+        let [keyword, name, assign, ..] = tokens else {
+            return None;
+        };
+        if keyword.text != "let"
+            || name.kind != TokenKind::Identifier
+            || assign.kind != TokenKind::Assign
+        {
+            return None;
+        }
+
+        let mut depth = 0;
+        let mut lets = 0;
+        let body = tokens.iter().enumerate().skip(3).position(|(_, token)| {
+            match (token.kind, token.text) {
+                (TokenKind::LeftParen, _) => depth += 1,
+                (TokenKind::RightParen, _) => depth -= 1,
+                (TokenKind::Keyword, "let") if depth == 0 => lets += 1,
+                (TokenKind::Keyword, "in") if depth == 0 && lets > 0 => lets -= 1,
+                (TokenKind::Keyword, "in") if depth == 0 => return true,
+                _ => {}
+            }
+            false
+        })? + 3;
+
+        Some(Node {
+            value: NodeValue::Operator("let".to_string()),
+            l_child: Some(Box::new(Node::binary(
+                "=",
+                Node::leaf(name.text),
+                Self::from_tokens_in(&tokens[3..body], left_to_right),
+            ))),
+            r_child: Some(Box::new(Self::from_tokens_in(
+                &tokens[body + 1..],
+                left_to_right,
+            ))),
+            span: Some((keyword.start, keyword.end)),
+        })
+    }
+
+    pub(crate) fn map_spans(&mut self, map: &dyn Fn((usize, usize)) -> (usize, usize)) {
+        self.span = self.span.map(map);
+        for child in [&mut self.l_child, &mut self.r_child].into_iter().flatten() {
+            child.map_spans(map);
         }
     }
 
+    pub(crate) fn binding(&self) -> Option<(&str, &Node)> {
+        if self.operator() != "let" {
+            return None;
+        }
+        let assignment = self.l_child.as_ref()?;
+        match (&assignment.l_child, &assignment.r_child) {
+            (Some(name), Some(value)) => match &name.value {
+                NodeValue::Variable(name) => Some((name, value)),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    fn from_comparison_chain(tokens: &[Token]) -> Self {
+        let mut depth = 0;
+        let mut comparisons = Vec::new();
+        for (index, token) in tokens.iter().enumerate() {
+            match token.kind {
+                TokenKind::LeftParen => depth += 1,
+                TokenKind::RightParen => depth -= 1,
+                TokenKind::Operator if depth == 0 && is_comparison(token.value()) => {
+                    comparisons.push(index)
+                }
+                _ => {}
+            }
+        }
+
+        let operand = |start: usize, end: usize| {
+            if start >= end {
+                None
+            } else {
+                Some(Box::new(Self::from_tokens(&tokens[start..end])))
+            }
+        };
+
+        let mut start = 0;
+        let mut chain: Option<Node> = None;
+        for (position, index) in comparisons.iter().enumerate() {
+            let end = comparisons
+                .get(position + 1)
+                .copied()
+                .unwrap_or(tokens.len());
+            let comparison = Node {
+                value: NodeValue::Operator(tokens[*index].value().to_string()),
+                l_child: operand(start, *index),
+                r_child: operand(index + 1, end),
+                span: Some((tokens[*index].start, tokens[*index].end)),
+            };
+
+            chain = Some(match chain {
+                None => comparison,
+                Some(chain) => Node::binary("&&", chain, comparison),
+            });
+            start = index + 1;
+        }
+
+        chain.unwrap_or_else(|| Self::from_tokens(tokens))
+    }
+
     pub fn evaluate(&self) -> Result<f64, NodeError> {
+        self.evaluate_in(&mut Evaluation::new(&HashMap::new()))
+    }
+
+    pub fn evaluate_checked(&self) -> Result<f64, NodeError> {
+        let value = self.evaluate()?;
+
+        if value.is_nan() {
+            Err(NodeError::DomainError(format!(
+                "'{}' is not a number",
+                self.to_infix()
+            )))
+        } else if value.is_infinite() {
+            Err(NodeError::Overflow)
+        } else {
+            Ok(value)
+        }
+    }
+
+    pub fn evaluate_sig_figs(&self, figs: usize) -> Result<f64, NodeError> {
+        if figs == 0 {
+            return Err(NodeError::DomainError(
+                "at least one significant figure is needed".to_string(),
+            ));
+        }
+
+        Ok(round_sig_figs(self.evaluate()?, figs))
+    }
+
+    pub fn evaluate_mod(&self, modulus: f64) -> Result<f64, NodeError> {
+        if !(modulus > 0.0 && modulus.is_finite()) {
+            return Err(NodeError::DomainError(format!(
+                "the modulus must be positive, got {}",
+                modulus
+            )));
+        }
+
+        let value = self.evaluate()?.rem_euclid(modulus);
+        Ok(if value == modulus { 0.0 } else { value })
+    }
+
+    pub fn evaluate_with_warnings(&self) -> Result<(f64, Vec<String>), NodeError> {
+        let value = self.evaluate()?;
+        let mut warnings = Vec::new();
+        if !value.is_finite() {
+            return Ok((value, warnings));
+        }
+
+        let rounded = round_sig_figs(value, 12);
+        if value.abs() > 2f64.powi(53) {
+            warnings.push(format!(
+                "precision: {} is too large for every integer to be exact",
+                value
+            ));
+        } else if rounded.is_finite() && rounded != value {
+            warnings.push(format!(
+                "precision: {} is probably {} with a rounding error",
+                value, rounded
+            ));
+        }
+        if value.fract() != 0.0 && (value - value.round()).abs() < 1e-9 * value.abs() {
+            warnings.push(format!(
+                "{} is very close to the integer {}",
+                value,
+                value.round()
+            ));
+        }
+        Ok((value, warnings))
+    }
+
+    pub fn evaluate_typed(&self) -> Result<(f64, bool), NodeError> {
+        let value = self.evaluate()?;
+        Ok((value, value.is_finite() && value.fract() == 0.0))
+    }
+
+    pub fn evaluate_with(&self, variables: &HashMap<String, f64>) -> Result<f64, NodeError> {
+        self.evaluate_in(&mut Evaluation::new(variables))
+    }
+
+    pub fn evaluate_with_default(
+        &self,
+        vars: &HashMap<String, f64>,
+        default: f64,
+    ) -> Result<f64, NodeError> {
+        let mut evaluation = Evaluation::new(vars);
+        evaluation.default = Some(default);
+
+        self.evaluate_in(&mut evaluation)
+    }
+
+    pub fn evaluate_with_context(&self, context: &Context) -> Result<f64, NodeError> {
+        let mut evaluation = Evaluation::new(context.variables());
+        evaluation.constants = Some(context.constants());
+        if !context.strict_identifiers() {
+            evaluation.default = Some(0.0);
+        }
+        evaluation.rounding_mode = context.rounding_mode();
+
+        let result = self.evaluate_in(&mut evaluation)?;
+        Ok(match context.rounding_mode() {
+            Some(mode) => mode.round(result),
+            None => result,
+        })
+    }
+
+    pub fn evaluate_with_step_limit(&self, step_limit: usize) -> Result<f64, NodeError> {
+        let variables = HashMap::new();
+        let mut evaluation = Evaluation::new(&variables);
+        evaluation.step_limit = step_limit;
+
+        self.evaluate_in(&mut evaluation)
+    }
+
+    pub fn evaluate_with_decimal_places(&self, decimal_places: u32) -> Result<f64, NodeError> {
+        let variables = HashMap::new();
+        let mut evaluation = Evaluation::new(&variables);
+        evaluation.decimal_places = Some(decimal_places);
+
+        self.evaluate_in(&mut evaluation)
+    }
+
+    pub fn numeric_derivative(
+        &self,
+        var: &str,
+        at: &HashMap<String, f64>,
+        h: f64,
+    ) -> Result<f64, NodeError> {
+        let x = *at
+            .get(var)
+            .ok_or_else(|| NodeError::UnknownVariable(var.to_string()))?;
+
+        let mut variables = at.clone();
+        variables.insert(var.to_string(), x + h);
+        let forward = self.evaluate_with(&variables)?;
+        variables.insert(var.to_string(), x - h);
+        let backward = self.evaluate_with(&variables)?;
+
+        Ok((forward - backward) / (2.0 * h))
+    }
+
+    pub fn evaluate_with_observer(
+        &self,
+        observer: &mut dyn FnMut(&Node, f64),
+    ) -> Result<f64, NodeError> {
+        let variables = HashMap::new();
+        let mut evaluation = Evaluation::new(&variables);
+        evaluation.observer = Some(observer);
+
+        self.evaluate_in(&mut evaluation)
+    }
+
+    fn evaluate_in(&self, evaluation: &mut Evaluation) -> Result<f64, NodeError> {
+        let value = self.evaluate_node(evaluation)?;
+        if let Some(observer) = &mut evaluation.observer {
+            observer(self, value);
+        }
+        Ok(value)
+    }
+
+    fn evaluate_node(&self, evaluation: &mut Evaluation) -> Result<f64, NodeError> {
         if !self.has_children() {
-            let value = self
-                .value
-                .parse::<f64>()
-                .map_err(|_| NodeError::InvalidExpression(self.value.clone()));
+            return evaluation.resolve(&self.value);
+        }
 
-            return value;
+        evaluation.steps += 1;
+        if evaluation.steps > evaluation.step_limit {
+            return Err(NodeError::StepLimitExceeded);
         }
 
-        let l_operand = match &self.l_child {
-            None => 0.0,
-            Some(l_child) => {
-                let res = l_child.evaluate();
+        if let Some((name, value)) = self.binding() {
+            let value = value.evaluate_in(evaluation)?;
+            evaluation.bindings.push((unquote(name).to_string(), value));
+            let result = self
+                .r_child
+                .as_ref()
+                .map(|body| body.evaluate_in(evaluation));
+            evaluation.bindings.pop();
+            return result.unwrap_or_else(|| Err(NodeError::InvalidExpression(self.to_infix())));
+        }
 
-                match res {
-                    Ok(op) => op,
-                    Err(err) => return Err(err),
-                }
+        if self.operator() == "sum_range" {
+            let arguments = self.arguments();
+            functions::check_arity(self.operator(), arguments.len())?;
+            let start = arguments[0].evaluate_in(evaluation)?;
+            let end = arguments[1].evaluate_in(evaluation)?;
+            let step = arguments[2].evaluate_in(evaluation)?;
+
+            let mut sum = 0.0;
+            for value in functions::range(start, end, step)? {
+                evaluation
+                    .bindings
+                    .push((functions::RANGE_VARIABLE.to_string(), value));
+                let term = arguments[3].evaluate_in(evaluation);
+                evaluation.bindings.pop();
+                sum = Self::round_to(sum + term?, evaluation.decimal_places);
             }
+            return Ok(sum);
+        }
+
+        if functions::is_function(self.operator()) {
+            let arguments = self
+                .arguments()
+                .iter()
+                .map(|argument| argument.evaluate_in(evaluation))
+                .collect::<Result<Vec<f64>, NodeError>>()?;
+
+            return functions::execute_function(self.operator(), &arguments)
+                .map(|result| Self::round_to(result, evaluation.decimal_places));
+        }
+
+        let l_operand = match &self.l_child {
+            None => 0.0,
+            Some(l_child) => l_child.evaluate_in(evaluation)?,
         };
 
         let r_operand = match &self.r_child {
             None => 0.0,
-            Some(r_child) => {
-                let res = r_child.evaluate();
-
-                match res {
-                    Ok(op) => op,
-                    Err(err) => return Err(err),
-                }
-            }
+            Some(r_child) => r_child.evaluate_in(evaluation)?,
         };
 
-        Self::execute_operation(&self.value, l_operand, r_operand)
+        let result = Self::execute_operation(
+            self.operator(),
+            l_operand,
+            r_operand,
+            evaluation.decimal_places,
+        )
+        .map_err(|err| match err {
+            NodeError::DivideByZero { .. } => NodeError::DivideByZero {
+                expression: self.to_infix(),
+            },
+            err => err,
+        })?;
+
+        Ok(match evaluation.rounding_mode {
+            Some(mode) if self.operator() == "/" => mode.round(result),
+            _ => result,
+        })
     }
 
     fn execute_operation(
-        operator: &String,
+        operator: &str,
         l_operand: f64,
         r_operand: f64,
+        decimal_places: Option<u32>,
     ) -> Result<f64, NodeError> {
-        match operator.as_str() {
+        let result = Self::execute_unrounded(operator, l_operand, r_operand)?;
+        Ok(Self::round_to(result, decimal_places))
+    }
+
+    fn round_to(result: f64, decimal_places: Option<u32>) -> f64 {
+        match decimal_places {
+            Some(places) if result.abs() < 2f64.powi(52) => {
+                let scale = 10f64.powi(places.min(f64::DIGITS + 1) as i32);
+                match result * scale {
+                    scaled if scaled.is_finite() => scaled.round() / scale,
+                    _ => result,
+                }
+            }
+            _ => result,
+        }
+    }
+
+    fn execute_unrounded(operator: &str, l_operand: f64, r_operand: f64) -> Result<f64, NodeError> {
+        match operator {
             "+" => Ok(l_operand + r_operand),
             "-" => Ok(l_operand - r_operand),
             "*" => Ok(l_operand * r_operand),
             "/" => {
                 if r_operand == 0.0 {
-                    Err(NodeError::DivideByZero)
+                    Err(NodeError::DivideByZero {
+                        expression: format!("{} / {}", l_operand, r_operand),
+                    })
                 } else {
                     Ok(l_operand / r_operand)
                 }
             }
             "^" => Ok(l_operand.powf(r_operand)),
+            "<" => Ok((l_operand < r_operand) as u8 as f64),
+            ">" => Ok((l_operand > r_operand) as u8 as f64),
+            "<=" => Ok((l_operand <= r_operand) as u8 as f64),
+            ">=" => Ok((l_operand >= r_operand) as u8 as f64),
+            "==" => Ok((l_operand == r_operand) as u8 as f64),
+            "!=" => Ok((l_operand != r_operand) as u8 as f64),
+            "&&" => Ok((l_operand != 0.0 && r_operand != 0.0) as u8 as f64),
             _ => Err(NodeError::InvalidExpression(format!(
                 "{} {} {}",
                 l_operand, operator, r_operand
@@ -121,159 +623,1684 @@ impl Node {
         }
     }
 
-    pub fn to_string(&self) -> String {
-        let mut result = self.value.clone() + if self.has_children() { "\n" } else { "" };
+    pub(crate) fn operator(&self) -> &str {
+        match &self.value {
+            NodeValue::Operator(operator) => operator,
+            _ => "",
+        }
+    }
+
+    pub(crate) fn arguments(&self) -> Vec<&Node> {
+        fn collect<'a>(node: &'a Node, arguments: &mut Vec<&'a Node>) {
+            if node.operator() == "," {
+                for child in [&node.l_child, &node.r_child].into_iter().flatten() {
+                    collect(child, arguments);
+                }
+            } else {
+                arguments.push(node);
+            }
+        }
 
-        if self.l_child.is_some() {
-            let l_child_string = self.l_child.as_ref().unwrap().to_string();
-            let l_child_rows = l_child_string.trim_end().split("\n").collect::<Vec<&str>>();
+        let mut arguments = Vec::new();
+        if let Some(r_child) = &self.r_child {
+            collect(r_child, &mut arguments);
+        }
+        arguments
+    }
 
-            if self.r_child.is_some() {
-                let r_child_string = self.r_child.as_ref().unwrap().to_string();
-                let r_child_rows: Vec<&str> = r_child_string.trim_end().split("\n").collect();
+    pub fn to_string_max_depth(&self, max_depth: usize) -> String {
+        self.render(max_depth, &|value| value.to_string())
+    }
 
-                for i in 0..l_child_rows.len() {
-                    if i == 0 {
-                        result.push_str("|-- ");
-                    } else {
-                        result.push_str("|   ");
-                    }
-                    result.push_str(l_child_rows[i]);
-                    result.push_str("\n");
-                }
+    pub fn to_string_colored(&self) -> String {
+        self.render(usize::MAX, &|value| {
+            let color = match value {
+                NodeValue::Number(_) => "36",
+                NodeValue::Operator(_) => "33",
+                NodeValue::Variable(_) => "32",
+            };
+            format!("\x1b[{}m{}\x1b[0m", color, value)
+        })
+    }
 
-                for i in 0..r_child_rows.len() {
-                    if i == 0 {
-                        result.push_str("`-- ");
-                    } else {
-                        result.push_str("    ");
-                    }
-                    result.push_str(r_child_rows[i]);
-                    result.push_str("\n");
-                }
+    fn render(&self, max_depth: usize, label: &dyn Fn(&NodeValue) -> String) -> String {
+        let mut result = label(&self.value) + if self.has_children() { "\n" } else { "" };
+
+        if max_depth == 0 && self.has_children() {
+            result.push_str(&format!("`-- ... ({} hidden)\n", self.node_count() - 1));
+            return result;
+        }
+
+        let children = [&self.l_child, &self.r_child]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<&Box<Node>>>();
+
+        for (index, child) in children.iter().enumerate() {
+            let (first_prefix, prefix) = if index == children.len() - 1 {
+                ("`-- ", "    ")
             } else {
-                for i in 0..l_child_rows.len() {
-                    if i == 0 {
-                        result.push_str("`-- ");
-                    } else {
-                        result.push_str("    ");
-                    }
-                    result.push_str(l_child_rows[i]);
-                    result.push_str("\n");
+                ("|-- ", "|   ")
+            };
+
+            let child_string = child.render(max_depth - 1, label);
+            for (i, row) in child_string.trim_end().split('\n').enumerate() {
+                if i == 0 {
+                    result.push_str(first_prefix);
+                } else {
+                    result.push_str(prefix);
                 }
+                result.push_str(row);
+                result.push('\n');
             }
         }
 
         result
     }
 
-    pub fn has_children(&self) -> bool {
-        match (&self.l_child, &self.r_child) {
-            (None, None) => false,
-            (_, _) => true,
+    pub fn precedence_depth(&self) -> usize {
+        fn deepest(node: &Node, levels: &mut Vec<u8>) -> usize {
+            let level = node
+                .binary_priority()
+                .filter(|level| !levels.contains(level));
+            levels.extend(level);
+
+            let depth = [&node.l_child, &node.r_child]
+                .into_iter()
+                .flatten()
+                .map(|child| deepest(child, levels))
+                .max()
+                .unwrap_or(levels.len());
+
+            if level.is_some() {
+                levels.pop();
+            }
+            depth
         }
+
+        deepest(self, &mut Vec::new())
     }
-}
 
-fn has_no_operators(expression: &String) -> bool {
-    for operator in OPERATORS.split("").collect::<Vec<&str>>() {
-        if expression.contains(operator) {
-            return false;
-        }
+    fn node_count(&self) -> usize {
+        1 + [&self.l_child, &self.r_child]
+            .into_iter()
+            .flatten()
+            .map(|child| child.node_count())
+            .sum::<usize>()
     }
-    true
-}
 
-fn split_on_lowest_priority_operator(expression: String) -> (String, String, String) {
-    if has_no_operators(&expression) {
-        return (expression, "".to_string(), "".to_string());
-    };
+    pub fn to_infix(&self) -> String {
+        if !self.has_children() {
+            return self.value.to_string();
+        }
 
-    let expression_copy = expression.clone();
-    let tokens = expression_copy.split(" ").collect::<Vec<&str>>();
-    let mut lowest_priority_operator_index: usize = 0;
-    let mut current_priority: u8 = 4;
-    for (index, &token) in tokens.iter().enumerate() {
-        match token {
-            "+" | "-" => {
-                lowest_priority_operator_index = index;
-                break;
-            }
-            "*" | "/" => {
-                if current_priority > 2 {
-                    lowest_priority_operator_index = index;
-                    current_priority = 2;
-                } else {
-                    continue;
-                }
+        if let (Some((name, value)), Some(body)) = (self.binding(), &self.r_child) {
+            return format!("let {} = {} in {}", name, value.to_infix(), body.to_infix());
+        }
+
+        if functions::is_function(self.operator()) {
+            let arguments = self
+                .arguments()
+                .iter()
+                .map(|argument| argument.to_infix())
+                .collect::<Vec<String>>();
+
+            return format!("{}({})", self.value, arguments.join(", "));
+        }
+
+        let operand = |child: &Node, wrap: bool| {
+            if wrap {
+                format!("({})", child.to_infix())
+            } else {
+                child.to_infix()
             }
-            "^" => {
-                if current_priority > 3 {
-                    lowest_priority_operator_index = index;
-                    current_priority = 3
-                } else {
-                    continue;
-                }
+        };
+
+        match (&self.l_child, &self.r_child) {
+            (Some(l_child), Some(r_child)) => {
+                let operator_priority = priority(self.operator()).unwrap_or(u8::MAX);
+                let wrap_l = l_child.is_unary_operation()
+                    || (self.operator() == "^"
+                        && matches!(l_child.value, NodeValue::Number(number) if number < 0.0))
+                    || l_child.binary_priority().is_some_and(|child_priority| {
+                        child_priority < operator_priority
+                            || (child_priority == operator_priority && self.operator() == "^")
+                    });
+                let wrap_r = r_child.is_unary_operation()
+                    || r_child.binary_priority().is_some_and(|child_priority| {
+                        child_priority < operator_priority
+                            || (child_priority == operator_priority && self.operator() != "^")
+                    });
+
+                format!(
+                    "{} {} {}",
+                    operand(l_child, wrap_l),
+                    self.value,
+                    operand(r_child, wrap_r)
+                )
             }
-            _ => continue,
+            (None, Some(r_child)) => format!(
+                "{}{}",
+                self.value,
+                operand(
+                    r_child,
+                    r_child.is_unary_operation() || r_child.binary_priority().is_some()
+                )
+            ),
+            (Some(l_child), None) => format!("{} {}", l_child.to_infix(), self.value),
+            (None, None) => self.value.to_string(),
         }
     }
-    if lowest_priority_operator_index == 0 {
-        (
-            tokens[lowest_priority_operator_index].to_string(),
-            "".to_string(),
-            tokens.split_first().unwrap().1.join(" "),
-        )
-    } else if lowest_priority_operator_index == tokens.len() {
-        (
-            tokens[lowest_priority_operator_index].to_string(),
-            tokens.split_last().unwrap().1.join(" "),
-            "".to_string(),
-        )
-    } else {
-        (
-            tokens[lowest_priority_operator_index].to_string(),
-            tokens.split_at(lowest_priority_operator_index).0.join(" "),
-            tokens
-                .split_at(lowest_priority_operator_index + 1)
-                .1
-                .join(" "),
+
+    pub fn to_json(&self) -> String {
+        let child = |child: &Option<Box<Node>>| match child {
+            Some(child) => child.to_json(),
+            None => "null".to_string(),
+        };
+
+        format!(
+            "{{\"value\":\"{}\",\"left\":{},\"right\":{}}}",
+            escape_json(&self.value.to_string()),
+            child(&self.l_child),
+            child(&self.r_child)
         )
     }
-}
 
-#[test]
-fn test_addition() {
-    let root = Node::from_expression("1 + 2".to_string());
-    assert!(root.evaluate().is_ok_and(|x| x == 3.0));
-}
+    pub fn to_edges(&self) -> Vec<(usize, usize)> {
+        fn collect(node: &Node, id: usize, next_id: &mut usize, edges: &mut Vec<(usize, usize)>) {
+            for child in [&node.l_child, &node.r_child].into_iter().flatten() {
+                let child_id = *next_id;
+                *next_id += 1;
+                edges.push((id, child_id));
+                collect(child, child_id, next_id, edges);
+            }
+        }
 
-#[test]
-fn test_subtraction() {
-    let root = Node::from_expression("1 - 2".to_string());
-    assert!(root.evaluate().is_ok_and(|x| x == -1.0));
-}
+        let mut edges = Vec::new();
+        collect(self, 0, &mut 1, &mut edges);
+        edges
+    }
 
-#[test]
-fn test_multiplication() {
-    let root = Node::from_expression("2 * 10".to_string());
-    assert!(root.evaluate().is_ok_and(|x| x == 20.0));
-}
+    pub fn labels(&self) -> Vec<String> {
+        let mut labels = vec![self.value.to_string()];
+        for child in [&self.l_child, &self.r_child].into_iter().flatten() {
+            labels.extend(child.labels());
+        }
+        labels
+    }
 
-#[test]
-fn test_division() {
-    let root = Node::from_expression("1 / 10".to_string());
-    assert!(root.evaluate().is_ok_and(|x| x == 0.1));
-}
+    pub fn to_mermaid(&self) -> String {
+        let mut mermaid = String::from("graph TD\n");
+        for (id, label) in self.labels().iter().enumerate() {
+            mermaid.push_str(&format!("    n{}[\"{}\"]\n", id, escape_mermaid(label)));
+        }
+        for (parent, child) in self.to_edges() {
+            mermaid.push_str(&format!("    n{} --> n{}\n", parent, child));
+        }
+        mermaid
+    }
 
-#[test]
-fn test_divide_by_zero() {
-    let root = Node::from_expression("1 / 0".to_string());
-    assert!(root.evaluate().is_err());
-}
+    fn binary_priority(&self) -> Option<u8> {
+        match (&self.l_child, &self.r_child) {
+            (Some(_), Some(_)) if self.operator() == "let" => Some(0),
+            (Some(_), Some(_)) => priority(self.operator()),
+            _ => None,
+        }
+    }
 
-#[test]
-fn test_invalid_expression() {
-    let root = Node::from_expression("expression".to_string());
-    assert!(root.evaluate().is_err());
+    fn is_unary_operation(&self) -> bool {
+        self.l_child.is_none() && self.r_child.is_some() && !functions::is_function(self.operator())
+    }
+
+    pub fn has_children(&self) -> bool {
+        match (&self.l_child, &self.r_child) {
+            (None, None) => false,
+            (_, _) => true,
+        }
+    }
+
+    pub fn literals(&self) -> Vec<f64> {
+        if let NodeValue::Number(number) = self.value {
+            return vec![number];
+        }
+
+        [&self.l_child, &self.r_child]
+            .into_iter()
+            .flatten()
+            .flat_map(|child| child.literals())
+            .collect()
+    }
+
+    pub fn variables(&self) -> Vec<&str> {
+        if let (Some((name, value)), Some(body)) = (self.binding(), &self.r_child) {
+            let mut variables = value.variables();
+            variables.extend(
+                body.variables()
+                    .into_iter()
+                    .filter(|var| *var != unquote(name)),
+            );
+            variables.sort_unstable();
+            variables.dedup();
+            return variables;
+        }
+
+        if let ("sum_range", [start, end, step, body]) =
+            (self.operator(), self.arguments().as_slice())
+        {
+            let mut variables = [start, end, step]
+                .into_iter()
+                .flat_map(|argument| argument.variables())
+                .collect::<Vec<&str>>();
+            variables.extend(
+                body.variables()
+                    .into_iter()
+                    .filter(|var| *var != functions::RANGE_VARIABLE),
+            );
+            variables.sort_unstable();
+            variables.dedup();
+            return variables;
+        }
+
+        let mut variables = match &self.value {
+            NodeValue::Variable(name)
+                if !self.has_children() && constant(unquote(name)).is_none() =>
+            {
+                vec![unquote(name)]
+            }
+            _ => Vec::new(),
+        };
+
+        for child in [&self.l_child, &self.r_child].into_iter().flatten() {
+            variables.extend(child.variables());
+        }
+        variables.sort_unstable();
+        variables.dedup();
+        variables
+    }
+
+    pub fn subexpressions(&self) -> Vec<String> {
+        let mut subexpressions = [&self.l_child, &self.r_child]
+            .into_iter()
+            .flatten()
+            .flat_map(|child| child.subexpressions())
+            .collect::<Vec<String>>();
+
+        if self.has_children() && self.operator() != "," {
+            subexpressions.push(self.to_infix());
+        }
+        subexpressions
+    }
+
+    pub fn common_subexpressions(&self) -> Vec<String> {
+        fn collect<'a>(node: &'a Node, subtrees: &mut Vec<&'a Node>) {
+            for child in [&node.l_child, &node.r_child].into_iter().flatten() {
+                collect(child, subtrees);
+            }
+            if node.has_children() && node.operator() != "," {
+                subtrees.push(node);
+            }
+        }
+
+        let mut subtrees = Vec::new();
+        collect(self, &mut subtrees);
+        let mut counts = HashMap::<&Node, usize>::new();
+        for subtree in &subtrees {
+            *counts.entry(subtree).or_default() += 1;
+        }
+
+        let mut common = Vec::new();
+        for subtree in subtrees {
+            if counts.remove(subtree).is_some_and(|count| count > 1) {
+                common.push(subtree.to_infix());
+            }
+        }
+        common
+    }
+
+    pub fn has_potential_div_by_zero(&self) -> bool {
+        let divides_by_zero = self.operator() == "/"
+            && self
+                .r_child
+                .as_ref()
+                .is_some_and(|denominator| !denominator.evaluate().is_ok_and(|x| x != 0.0));
+
+        divides_by_zero
+            || [&self.l_child, &self.r_child]
+                .into_iter()
+                .flatten()
+                .any(|child| child.has_potential_div_by_zero())
+    }
+
+    pub fn denominators(&self) -> Vec<String> {
+        let mut denominators = match (self.operator(), &self.l_child, &self.r_child) {
+            ("/", Some(_), Some(denominator)) => vec![denominator.to_infix()],
+            _ => Vec::new(),
+        };
+        for child in [&self.l_child, &self.r_child].into_iter().flatten() {
+            denominators.extend(child.denominators());
+        }
+        denominators
+    }
+
+    pub fn substitute(&self, var: &str, replacement: &Node) -> Node {
+        if let NodeValue::Variable(name) = &self.value {
+            if !self.has_children() && unquote(name) == unquote(var) {
+                return replacement.clone();
+            }
+        }
+
+        if let (Some((name, value)), Some(body)) = (self.binding(), &self.r_child) {
+            let body = if unquote(name) == unquote(var) {
+                body.as_ref().clone()
+            } else {
+                body.substitute(var, replacement)
+            };
+            return Node {
+                value: self.value.clone(),
+                l_child: Some(Box::new(Node::binary(
+                    "=",
+                    Node::leaf(name),
+                    value.substitute(var, replacement),
+                ))),
+                r_child: Some(Box::new(body)),
+                span: self.span,
+            };
+        }
+
+        if self.operator() == "sum_range" && var == functions::RANGE_VARIABLE {
+            if let Some(body) = self.arguments().get(3) {
+                return Node {
+                    value: self.value.clone(),
+                    l_child: self.l_child.clone(),
+                    r_child: self.r_child.as_ref().map(|r_child| {
+                        Box::new(r_child.substitute_arguments(var, replacement, body))
+                    }),
+                    span: self.span,
+                };
+            }
+        }
+
+        Node {
+            value: self.value.clone(),
+            l_child: self
+                .l_child
+                .as_ref()
+                .map(|l_child| Box::new(l_child.substitute(var, replacement))),
+            r_child: self
+                .r_child
+                .as_ref()
+                .map(|r_child| Box::new(r_child.substitute(var, replacement))),
+            span: self.span,
+        }
+    }
+
+    fn substitute_arguments(&self, var: &str, replacement: &Node, body: &Node) -> Node {
+        if std::ptr::eq(self, body) {
+            return self.clone();
+        }
+        if self.operator() != "," {
+            return self.substitute(var, replacement);
+        }
+
+        Node {
+            value: self.value.clone(),
+            l_child: self
+                .l_child
+                .as_ref()
+                .map(|l_child| Box::new(l_child.substitute_arguments(var, replacement, body))),
+            r_child: self
+                .r_child
+                .as_ref()
+                .map(|r_child| Box::new(r_child.substitute_arguments(var, replacement, body))),
+            span: self.span,
+        }
+    }
+
+    pub fn normalize(&self) -> Node {
+        let mut l_child = self
+            .l_child
+            .as_ref()
+            .map(|l_child| Box::new(l_child.normalize()));
+        let mut r_child = self
+            .r_child
+            .as_ref()
+            .map(|r_child| Box::new(r_child.normalize()));
+
+        if let (Some(l), Some(r)) = (&l_child, &r_child) {
+            if (self.operator() == "+" || self.operator() == "*") && l.to_infix() > r.to_infix() {
+                std::mem::swap(&mut l_child, &mut r_child);
+            }
+        }
+
+        Node {
+            value: self.value.clone(),
+            l_child,
+            r_child,
+            span: self.span,
+        }
+    }
+
+    pub fn desugar_subtraction(&self) -> Node {
+        let desugar = |child: &Option<Box<Node>>| {
+            child
+                .as_ref()
+                .map(|child| Box::new(child.desugar_subtraction()))
+        };
+        let (l_child, r_child) = (desugar(&self.l_child), desugar(&self.r_child));
+
+        match (self.operator(), l_child, r_child) {
+            ("-", Some(l_child), Some(r_child)) => {
+                let negated = match r_child.value {
+                    NodeValue::Number(number) if !r_child.has_children() => Node::number(-number),
+                    _ => Node {
+                        value: NodeValue::Operator("-".to_string()),
+                        l_child: None,
+                        r_child: Some(r_child),
+                        span: None,
+                    },
+                };
+                Node::binary("+", *l_child, negated)
+            }
+            ("/", Some(l_child), Some(r_child)) => Node::binary(
+                "*",
+                *l_child,
+                Node::binary("^", *r_child, Node::number(-1.0)),
+            ),
+            (_, l_child, r_child) => Node {
+                value: self.value.clone(),
+                l_child,
+                r_child,
+                span: self.span,
+            },
+        }
+    }
+
+    fn has_empty_argument(&self) -> bool {
+        self.operator() == ","
+            && [&self.l_child, &self.r_child].into_iter().any(|child| {
+                child
+                    .as_ref()
+                    .is_none_or(|child| child.has_empty_argument())
+            })
+    }
+
+    pub fn validate(&self) -> Result<(), NodeError> {
+        if !self.has_children() {
+            return match &self.value {
+                NodeValue::Number(_) => Ok(()),
+                NodeValue::Variable(name) if is_identifier(name) || is_quoted_identifier(name) => {
+                    Ok(())
+                }
+                value => Err(NodeError::InvalidExpression(value.to_string())),
+            };
+        }
+
+        if self.operator() == "let" {
+            return match (self.binding(), &self.r_child) {
+                (Some((name, value)), Some(body))
+                    if is_identifier(name) || is_quoted_identifier(name) =>
+                {
+                    value.validate()?;
+                    body.validate()
+                }
+                _ => Err(NodeError::InvalidExpression("malformed 'let'".to_string())),
+            };
+        }
+
+        if functions::is_function(self.operator()) {
+            if self
+                .r_child
+                .as_ref()
+                .is_some_and(|r_child| r_child.has_empty_argument())
+            {
+                return Err(NodeError::InvalidExpression(format!(
+                    "empty argument in '{}'",
+                    self.operator()
+                )));
+            }
+            let arguments = self.arguments();
+            functions::check_arity(self.operator(), arguments.len())?;
+
+            for argument in arguments {
+                argument.validate()?;
+            }
+            return Ok(());
+        }
+
+        if self.operator() == "," {
+            return Err(NodeError::InvalidExpression(
+                "',' outside of a function call".to_string(),
+            ));
+        }
+
+        if !SUPPORTED_OPERATORS.contains(&self.operator()) {
+            return Err(NodeError::InvalidExpression(format!(
+                "'{}' is not an operator",
+                self.value
+            )));
+        }
+
+        match (&self.l_child, &self.r_child) {
+            (Some(l_child), Some(r_child)) => {
+                l_child.validate()?;
+                r_child.validate()
+            }
+            (None, Some(r_child)) if self.operator() == "+" || self.operator() == "-" => {
+                r_child.validate()
+            }
+            _ => Err(NodeError::InvalidExpression(format!(
+                "missing operand for '{}'",
+                self.value
+            ))),
+        }
+    }
+}
+
+type Observer<'a> = dyn FnMut(&Node, f64) + 'a;
+
+struct Evaluation<'a> {
+    constants: Option<&'a HashMap<String, f64>>,
+    variables: &'a HashMap<String, f64>,
+    default: Option<f64>,
+    steps: usize,
+    step_limit: usize,
+    decimal_places: Option<u32>,
+    rounding_mode: Option<RoundingMode>,
+    observer: Option<&'a mut Observer<'a>>,
+    bindings: Vec<(String, f64)>,
+}
+
+impl<'a> Evaluation<'a> {
+    fn new(variables: &'a HashMap<String, f64>) -> Self {
+        Evaluation {
+            constants: None,
+            variables,
+            default: None,
+            steps: 0,
+            step_limit: usize::MAX,
+            decimal_places: None,
+            rounding_mode: None,
+            observer: None,
+            bindings: Vec::new(),
+        }
+    }
+
+    fn resolve(&self, value: &NodeValue) -> Result<f64, NodeError> {
+        let leaf = match value {
+            NodeValue::Number(number) => return Ok(*number),
+            NodeValue::Operator(operator) => {
+                return Err(NodeError::InvalidExpression(operator.to_string()))
+            }
+            NodeValue::Variable(name) if is_quoted_identifier(name) => unquote(name),
+            NodeValue::Variable(name) => name.as_str(),
+        };
+
+        if let Some((_, value)) = self.bindings.iter().rev().find(|(name, _)| name == leaf) {
+            return Ok(*value);
+        }
+
+        let constant = match self.constants {
+            Some(constants) => constants.get(leaf).copied(),
+            None => constant(leaf),
+        };
+        if let Some(value) = constant {
+            return Ok(value);
+        }
+
+        match self.variables.get(leaf) {
+            Some(value) => Ok(*value),
+            None if is_identifier(leaf) || leaf != value.to_string() => self
+                .default
+                .ok_or_else(|| NodeError::UnknownVariable(leaf.to_string())),
+            None => Err(NodeError::InvalidExpression(leaf.to_string())),
+        }
+    }
+}
+
+pub(crate) fn constant(name: &str) -> Option<f64> {
+    CONSTANTS
+        .iter()
+        .find(|(constant, _)| *constant == name)
+        .map(|(_, value)| *value)
+}
+
+pub fn parse(input: &str) -> Result<Node, NodeError> {
+    Parser::default().parse(input)
+}
+
+pub fn evaluate_str(input: &str) -> Result<f64, NodeError> {
+    parse(input)?.evaluate()
+}
+
+pub fn tree_diff(a: &Node, b: &Node) -> Vec<String> {
+    fn diff(a: &Node, b: &Node, path: &str, changes: &mut Vec<String>) {
+        if a == b {
+            return;
+        }
+
+        let same_shape = a.l_child.is_some() == b.l_child.is_some()
+            && a.r_child.is_some() == b.r_child.is_some();
+        if !a.has_children() || !b.has_children() || !same_shape {
+            changes.push(format!("{}: {} -> {}", path, a.to_infix(), b.to_infix()));
+            return;
+        }
+
+        if a.value != b.value {
+            changes.push(format!("{}: operator '{}' -> '{}'", path, a.value, b.value));
+        }
+        for (side, a_child, b_child) in [
+            ("left", &a.l_child, &b.l_child),
+            ("right", &a.r_child, &b.r_child),
+        ] {
+            if let (Some(a_child), Some(b_child)) = (a_child, b_child) {
+                diff(a_child, b_child, &format!("{}.{}", path, side), changes);
+            }
+        }
+    }
+
+    let mut changes = Vec::new();
+    diff(a, b, "root", &mut changes);
+    changes
+}
+
+pub fn apply_template(template: &str, args: &[f64]) -> Result<f64, NodeError> {
+    let mut expression = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        expression.push_str(&rest[..open]);
+        let close = rest[open..].find('}').ok_or_else(|| {
+            NodeError::InvalidExpression(format!("unclosed '{{' in '{}'", template))
+        })?;
+        let placeholder = &rest[open + 1..open + close];
+        let value = placeholder
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| args.get(index))
+            .ok_or_else(|| {
+                NodeError::InvalidExpression(format!(
+                    "'{{{}}}' doesn't match any of the {} arguments",
+                    placeholder,
+                    args.len()
+                ))
+            })?;
+        expression.push_str(&format!("({:?})", value));
+        rest = &rest[open + close + 1..];
+    }
+    expression.push_str(rest);
+
+    evaluate_str(&expression)
+}
+
+fn round_sig_figs(value: f64, figs: usize) -> f64 {
+    if value == 0.0 || !value.is_finite() {
+        return value;
+    }
+
+    let figs = figs.clamp(1, 17);
+    format!("{:.*e}", figs - 1, value)
+        .parse::<f64>()
+        .ok()
+        .filter(|rounded| rounded.is_finite())
+        .unwrap_or(value)
+}
+
+pub fn approx_eq(a: f64, b: f64, rel_tol: f64) -> bool {
+    a == b || (a - b).abs() <= rel_tol * a.abs().max(b.abs())
+}
+
+fn parse_number(literal: &str) -> Option<f64> {
+    match literal {
+        "inf" => Some(f64::INFINITY),
+        "-inf" => Some(f64::NEG_INFINITY),
+        "nan" => Some(f64::NAN),
+        _ if literal
+            .chars()
+            .any(|c| c.is_alphabetic() && c != 'e' && c != 'E') =>
+        {
+            None
+        }
+        _ => literal.parse::<f64>().ok(),
+    }
+}
+
+fn escape_json(value: &str) -> String {
+    let mut escaped = String::new();
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn is_identifier(token: &str) -> bool {
+    token
+        .chars()
+        .next()
+        .is_some_and(|c| c.is_alphabetic() || c == '_')
+        && token.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn is_quoted_identifier(token: &str) -> bool {
+    token.len() > 2
+        && token.starts_with('"')
+        && token.ends_with('"')
+        && !token[1..token.len() - 1].contains('"')
+}
+
+pub(crate) fn unquote(name: &str) -> &str {
+    match name
+        .strip_prefix('"')
+        .and_then(|name| name.strip_suffix('"'))
+    {
+        Some(unquoted) => unquoted,
+        None => name,
+    }
+}
+
+fn is_wrapped_in_parentheses(tokens: &[Token]) -> bool {
+    if tokens.len() < 2
+        || tokens[0].kind != TokenKind::LeftParen
+        || tokens[tokens.len() - 1].kind != TokenKind::RightParen
+    {
+        return false;
+    }
+
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            _ => {}
+        }
+        if depth == 0 {
+            return index == tokens.len() - 1;
+        }
+    }
+    false
+}
+
+pub fn precedence(operator: &str) -> Option<u8> {
+    match operator {
+        "&&" => Some(1),
+        "<" | ">" | "<=" | ">=" | "==" | "!=" => Some(2),
+        "+" | "-" => Some(3),
+        "*" | "/" => Some(4),
+        "^" => Some(5),
+        _ => None,
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Associativity {
+    Left,
+    Right,
+    Chained,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OperatorInfo {
+    pub symbol: &'static str,
+    pub precedence: u8,
+    pub associativity: Associativity,
+    pub arity: usize,
+}
+
+pub fn operator_info() -> Vec<OperatorInfo> {
+    let binary = SUPPORTED_OPERATORS.iter().filter_map(|symbol| {
+        Some(OperatorInfo {
+            symbol,
+            precedence: precedence(symbol)?,
+            associativity: match *symbol {
+                "^" => Associativity::Right,
+                _ if is_comparison(symbol) => Associativity::Chained,
+                _ => Associativity::Left,
+            },
+            arity: 2,
+        })
+    });
+    let unary = ["+", "-"].into_iter().filter_map(|symbol| {
+        Some(OperatorInfo {
+            symbol,
+            precedence: precedence("^")?,
+            associativity: Associativity::Right,
+            arity: 1,
+        })
+    });
+    binary.chain(unary).collect()
+}
+
+fn is_comparison(operator: &str) -> bool {
+    precedence(operator) == precedence("==")
+}
+
+fn priority(operator: &str) -> Option<u8> {
+    match operator {
+        "," => Some(0),
+        _ => precedence(operator),
+    }
+}
+
+fn split_on_lowest_priority_operator(tokens: &[Token], left_to_right: bool) -> Option<usize> {
+    let mut lowest_priority_operator_index = None;
+    let mut current_priority = u8::MAX;
+    let mut depth = 0;
+
+    for (index, token) in tokens.iter().enumerate() {
+        match token.kind {
+            TokenKind::LeftParen => depth += 1,
+            TokenKind::RightParen => depth -= 1,
+            _ if depth != 0 || is_unary_prefix(tokens, index) => continue,
+            _ => {
+                if let Some(operator_priority) = priority(token.value()) {
+                    let operator_priority = match token.value() {
+                        "," => operator_priority,
+                        _ if left_to_right => 1,
+                        _ => operator_priority,
+                    };
+                    let is_right_associative = token.value() == "^" && !left_to_right;
+                    if operator_priority < current_priority
+                        || (operator_priority == current_priority && !is_right_associative)
+                    {
+                        lowest_priority_operator_index = Some(index);
+                        current_priority = operator_priority;
+                    }
+                }
+            }
+        }
+    }
+
+    let binds_looser_than_prefix = current_priority <= precedence("*").unwrap_or(u8::MAX);
+    if is_unary_prefix(tokens, 0) && !binds_looser_than_prefix {
+        return Some(0);
+    }
+    lowest_priority_operator_index
+}
+
+fn escape_mermaid(label: &str) -> String {
+    label
+        .chars()
+        .map(|c| match c {
+            '"' => "#quot;".to_string(),
+            '<' => "#lt;".to_string(),
+            '>' => "#gt;".to_string(),
+            '#' => "#35;".to_string(),
+            c => c.to_string(),
+        })
+        .collect()
+}
+
+pub(crate) fn is_unary_prefix(tokens: &[Token], index: usize) -> bool {
+    let Some(token) = tokens.get(index) else {
+        return false;
+    };
+    if token.kind != TokenKind::Operator || (token.text != "-" && token.text != "+") {
+        return false;
+    }
+
+    index == 0
+        || match tokens[index - 1].kind {
+            TokenKind::Operator
+            | TokenKind::LeftParen
+            | TokenKind::Comma
+            | TokenKind::Assign
+            | TokenKind::Keyword => true,
+            TokenKind::Identifier => functions::is_function(tokens[index - 1].text),
+            _ => false,
+        }
+}
+
+#[test]
+fn test_addition() {
+    let root = Node::from_expression("1 + 2".to_string());
+    assert!(root.evaluate().is_ok_and(|x| x == 3.0));
+}
+
+#[test]
+fn test_subtraction() {
+    let root = Node::from_expression("1 - 2".to_string());
+    assert!(root.evaluate().is_ok_and(|x| x == -1.0));
+}
+
+#[test]
+fn test_multiplication() {
+    let root = Node::from_expression("2 * 10".to_string());
+    assert!(root.evaluate().is_ok_and(|x| x == 20.0));
+}
+
+#[test]
+fn test_division() {
+    let root = Node::from_expression("1 / 10".to_string());
+    assert!(root.evaluate().is_ok_and(|x| x == 0.1));
+}
+
+#[test]
+fn test_divide_by_zero() {
+    let root = Node::from_expression("1 / 0".to_string());
+    assert!(root.evaluate().is_err());
+}
+
+#[test]
+fn test_invalid_expression() {
+    let root = Node::from_expression("expression".to_string());
+    assert!(root.evaluate().is_err());
+}
+
+#[test]
+fn test_parse_does_not_evaluate() {
+    assert!(parse("1 / 0").is_ok());
+    assert!(evaluate_str("1 / 0").is_err());
+}
+
+#[test]
+fn test_parse_rejects_missing_operand() {
+    assert!(parse("2 *").is_err());
+    assert!(parse("* 2").is_err());
+    assert!(parse("- 2").is_ok());
+}
+
+#[test]
+fn test_gcd() {
+    assert!(evaluate_str("gcd(12, 18)").is_ok_and(|x| x == 6.0));
+    assert!(evaluate_str("gcd(1.5, 3)").is_err());
+}
+
+#[test]
+fn test_lcm() {
+    assert!(evaluate_str("lcm(4, 6)").is_ok_and(|x| x == 12.0));
+    assert!(evaluate_str("lcm(4)").is_err());
+}
+
+#[test]
+fn test_step_limit() {
+    let root = Node::from_expression("1 + 2 * 3 - 4 / 2".to_string());
+    assert!(root.evaluate_with_step_limit(4).is_ok_and(|x| x == 5.0));
+    assert!(matches!(
+        root.evaluate_with_step_limit(3),
+        Err(NodeError::StepLimitExceeded)
+    ));
+}
+
+#[test]
+fn test_substitute() {
+    let root = Node::from_expression("x * 2".to_string());
+    let replacement = Node::from_expression("a + 1".to_string());
+    let substituted = root.substitute("x", &replacement);
+
+    let variables = HashMap::from([("a".to_string(), 4.0)]);
+    assert!(substituted
+        .evaluate_with(&variables)
+        .is_ok_and(|x| x == 10.0));
+    assert!(matches!(
+        root.evaluate_with(&variables),
+        Err(NodeError::UnknownVariable(_))
+    ));
+
+    let root = parse("sum_range(1, 3, 1, i)").unwrap();
+    assert!(root
+        .substitute("i", &parse("3").unwrap())
+        .evaluate()
+        .is_ok_and(|x| x == 6.0));
+    assert!(parse("sum_range(1, i, 1, i)")
+        .unwrap()
+        .substitute("i", &parse("3").unwrap())
+        .evaluate()
+        .is_ok_and(|x| x == 6.0));
+
+    let root = parse("\"total cost\" * 2").unwrap();
+    assert!(root
+        .substitute("total cost", &parse("5").unwrap())
+        .evaluate()
+        .is_ok_and(|x| x == 10.0));
+    assert!(parse("let \"total cost\" = 1 in \"total cost\" * 2")
+        .unwrap()
+        .substitute("total cost", &parse("5").unwrap())
+        .evaluate()
+        .is_ok_and(|x| x == 2.0));
+}
+
+#[test]
+fn test_to_edges() {
+    let root = Node::from_expression("1 + 2 * 3".to_string());
+    assert_eq!(root.to_edges(), vec![(0, 1), (0, 2), (2, 3), (2, 4)]);
+    assert_eq!(root.labels(), vec!["+", "1", "*", "2", "3"]);
+    assert!(Node::leaf("x").to_edges().is_empty());
+}
+
+#[test]
+fn test_variables() {
+    let root = Node::from_expression("y * x + pi - max(x, z)".to_string());
+    assert_eq!(root.variables(), vec!["x", "y", "z"]);
+}
+
+#[test]
+fn test_has_potential_div_by_zero() {
+    let flagged =
+        |input: &str| Node::from_expression(input.to_string()).has_potential_div_by_zero();
+    assert!(!flagged("1 / 5"));
+    assert!(!flagged("x / (pi - 3) + 2"));
+    assert!(flagged("1 / x"));
+    assert!(flagged("1 / (x - x)"));
+    assert!(flagged("2 * (3 + 1 / (2 - 2))"));
+}
+
+#[test]
+fn test_special_float_literals() {
+    assert_eq!(Node::leaf("inf").value, NodeValue::Number(f64::INFINITY));
+    assert_eq!(
+        Node::leaf("-inf").value,
+        NodeValue::Number(f64::NEG_INFINITY)
+    );
+    assert!(matches!(Node::leaf("nan").value, NodeValue::Number(x) if x.is_nan()));
+    assert_eq!(
+        Node::leaf("infinity").value,
+        NodeValue::Variable("infinity".to_string())
+    );
+
+    assert!(evaluate_str("-inf").is_ok_and(|x| x == f64::NEG_INFINITY));
+    assert!(evaluate_str("inf - inf").is_ok_and(|x| x.is_nan()));
+    assert!(parse("inf - inf").unwrap().evaluate_checked().is_err());
+    assert!(parse("1 / inf")
+        .unwrap()
+        .evaluate_checked()
+        .is_ok_and(|x| x == 0.0));
+}
+
+#[test]
+fn test_structural_hash() {
+    let hash = |node: &Node| {
+        let mut hasher = std::collections::hash_map::DefaultHasher::new();
+        node.hash(&mut hasher);
+        hasher.finish()
+    };
+
+    let a = Node::from_expression("1 + 2".to_string());
+    let b = parse("(1 + 2.0)").unwrap();
+    assert_eq!(a, b);
+    assert_eq!(hash(&a), hash(&b));
+    assert_eq!(Node::leaf("nan"), Node::leaf("nan"));
+    assert_eq!(Node::leaf("-0"), Node::leaf("0"));
+
+    let mut cache = HashMap::new();
+    cache.insert(a, 3.0);
+    assert_eq!(cache.get(&b), Some(&3.0));
+    assert!(!cache.contains_key(&parse("2 + 1").unwrap()));
+}
+
+#[test]
+fn test_to_string_max_depth() {
+    let root = Node::from_expression("(1 + 2) * 3 - 4".to_string());
+    assert_eq!(root.to_string_max_depth(0), "-\n`-- ... (6 hidden)\n");
+    assert_eq!(
+        root.to_string_max_depth(1),
+        "-\n|-- *\n|   `-- ... (4 hidden)\n`-- 4\n"
+    );
+    assert_eq!(root.to_string_max_depth(3), root.to_string());
+}
+
+#[test]
+fn test_precedence_depth() {
+    let depth = |input: &str| Node::from_expression(input.to_string()).precedence_depth();
+    assert_eq!(depth("1 + 2 * 3 ^ 4"), 3);
+    assert_eq!(depth("1 + 2 + 3"), 1);
+    assert_eq!(depth("(1 + 2) * (3 - 4) + 5"), 2);
+    assert_eq!(depth("x"), 0);
+}
+
+#[test]
+fn test_numeric_derivative() {
+    let at = HashMap::from([("x".to_string(), 3.0), ("y".to_string(), 0.0)]);
+    let root = Node::from_expression("x ^ 2".to_string());
+    assert!(root
+        .numeric_derivative("x", &at, 1e-5)
+        .is_ok_and(|x| approx_eq(x, 6.0, 1e-6)));
+
+    let root = Node::from_expression("sin(y) * x".to_string());
+    assert!(root
+        .numeric_derivative("y", &at, 1e-5)
+        .is_ok_and(|x| approx_eq(x, 3.0, 1e-6)));
+    assert!(root.numeric_derivative("z", &at, 1e-5).is_err());
+}
+
+#[test]
+fn test_evaluate_with_observer() {
+    let mut visited = Vec::new();
+    let root = Node::from_expression("1 + 2".to_string());
+    let result =
+        root.evaluate_with_observer(&mut |node, value| visited.push((node.to_infix(), value)));
+
+    assert!(result.is_ok_and(|x| x == 3.0));
+    assert_eq!(
+        visited,
+        vec![
+            ("1".to_string(), 1.0),
+            ("2".to_string(), 2.0),
+            ("1 + 2".to_string(), 3.0)
+        ]
+    );
+
+    let mut count = 0;
+    let root = Node::from_expression("max(1, 2 * 3)".to_string());
+    assert!(root.evaluate_with_observer(&mut |_, _| count += 1).is_ok());
+    assert_eq!(count, 5);
+}
+
+#[test]
+fn test_to_string_colored() {
+    let root = Node::from_expression("x * 2".to_string());
+    assert_eq!(
+        root.to_string_colored(),
+        "\x1b[33m*\x1b[0m\n|-- \x1b[32mx\x1b[0m\n`-- \x1b[36m2\x1b[0m\n"
+    );
+    assert!(!root.to_string().contains('\x1b'));
+}
+
+#[test]
+fn test_subexpressions() {
+    let root = Node::from_expression("1 + 2 * 3".to_string());
+    assert_eq!(root.subexpressions(), vec!["2 * 3", "1 + 2 * 3"]);
+
+    let root = Node::from_expression("max(x - 1, 2) / (-y)".to_string());
+    assert_eq!(
+        root.subexpressions(),
+        vec!["x - 1", "max(x - 1, 2)", "-y", "max(x - 1, 2) / (-y)"]
+    );
+    assert!(Node::leaf("x").subexpressions().is_empty());
+}
+
+#[test]
+fn test_evaluate_sig_figs() {
+    let sig_figs = |input: &str, figs: usize| {
+        Node::from_expression(input.to_string())
+            .evaluate_sig_figs(figs)
+            .unwrap()
+    };
+    assert_eq!(sig_figs("1 / 3", 3), 0.333);
+    assert_eq!(sig_figs("1234.5", 2), 1200.0);
+    assert_eq!(sig_figs("-0.0045678", 2), -0.0046);
+    assert_eq!(sig_figs("999.7", 3), 1000.0);
+    assert_eq!(sig_figs("0", 3), 0.0);
+    assert!(Node::leaf("1").evaluate_sig_figs(0).is_err());
+
+    assert_eq!(sig_figs("5e-324", 3), 5e-324);
+    assert_eq!(sig_figs("2.2250738585072014e-308 / 3", 2), 7.4e-309);
+    assert_eq!(sig_figs("1 / 3", 400), 1.0 / 3.0);
+    assert_eq!(sig_figs("1.7976931348623157e308", 17), f64::MAX);
+    assert_eq!(sig_figs("1.7976931348623157e308", 2), f64::MAX);
+}
+
+#[test]
+fn test_desugar_subtraction() {
+    let root = Node::from_expression("5 - 3".to_string()).desugar_subtraction();
+    assert_eq!(root.operator(), "+");
+    assert!(root
+        .r_child
+        .as_ref()
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == -3.0));
+    assert!(root.evaluate().is_ok_and(|x| x == 2.0));
+
+    let root = Node::from_expression("(x - y) / 4 - 1 / x".to_string()).desugar_subtraction();
+    assert_eq!(root.to_infix(), "(x + (-y)) * 4 ^ -1 + (-(1 * x ^ -1))");
+    let variables = HashMap::from([("x".to_string(), 2.0), ("y".to_string(), 6.0)]);
+    assert!(root.evaluate_with(&variables).is_ok_and(|x| x == -1.5));
+}
+
+#[test]
+fn test_clone() {
+    let root = Node::from_expression("2 * 3 + 1".to_string());
+    let mut clone = root.clone();
+    assert!(clone.evaluate().is_ok_and(|x| x == 7.0));
+
+    clone.value = NodeValue::Operator("-".to_string());
+    assert!(clone.evaluate().is_ok_and(|x| x == 5.0));
+    assert!(root.evaluate().is_ok_and(|x| x == 7.0));
+}
+
+#[test]
+fn test_constructors() {
+    let root = Node::binary(
+        "*",
+        Node::binary("+", Node::leaf("1"), Node::leaf("2")),
+        Node::leaf("4"),
+    );
+    assert!(root.validate().is_ok());
+    assert!(root.evaluate().is_ok_and(|x| x == 12.0));
+    assert!(!Node::leaf("4").has_children());
+    assert_eq!(Node::leaf("4").value, NodeValue::Number(4.0));
+    assert_eq!(Node::leaf("x").value, NodeValue::Variable("x".to_string()));
+}
+
+#[test]
+fn test_to_infix() {
+    let root = Node::from_expression("(1 + 2) * 3 ^ (4 - x) - gcd(6, 4)".to_string());
+    assert_eq!(root.to_infix(), "(1 + 2) * 3 ^ (4 - x) - gcd(6, 4)");
+    assert_eq!(
+        Node::from_expression("2 * (-(4 - 1))".to_string()).to_infix(),
+        "2 * (-(4 - 1))"
+    );
+    let power = Node::binary("^", Node::number(-3.0), Node::number(2.0));
+    assert_eq!(power.to_infix(), "(-3) ^ 2");
+    assert_eq!(crate::parse(&power.to_infix()).unwrap(), power);
+}
+
+#[test]
+fn test_divide_by_zero_context() {
+    let root = Node::from_expression("10 + 1 / 2 * (5 / (3 - 3))".to_string());
+    match root.evaluate() {
+        Err(NodeError::DivideByZero { expression }) => assert_eq!(expression, "5 / (3 - 3)"),
+        _ => panic!("expected a division by zero"),
+    }
+}
+
+#[test]
+fn test_normalize() {
+    let a = Node::from_expression("2 + x".to_string());
+    let b = Node::from_expression("x + 2".to_string());
+    assert_ne!(a, b);
+    assert_eq!(a.normalize(), b.normalize());
+
+    let a = Node::from_expression("y * (3 + x) - 1".to_string());
+    let b = Node::from_expression("(x + 3) * y - 1".to_string());
+    assert_eq!(a.normalize(), b.normalize());
+    assert_ne!(
+        Node::from_expression("x - 2".to_string()).normalize(),
+        Node::from_expression("2 - x".to_string()).normalize()
+    );
+}
+
+#[test]
+fn test_approx_eq() {
+    assert!(approx_eq(0.1 + 0.2, 0.3, 1e-12));
+    assert!(approx_eq(1e20, 1e20 + 1e5, 1e-12));
+    assert!(approx_eq(f64::INFINITY, f64::INFINITY, 1e-12));
+    assert!(!approx_eq(1.0, 1.1, 1e-3));
+    assert!(!approx_eq(0.0, 1e-20, 1e-3));
+
+    let root = Node::from_expression("0.1 + 0.2 - 0.2".to_string());
+    assert!(root.evaluate().is_ok_and(|x| approx_eq(x, 0.1, 1e-9)));
+}
+
+#[test]
+fn test_precedence() {
+    assert!(precedence("+") < precedence("*"));
+    assert!(precedence("*") < precedence("^"));
+    assert_eq!(precedence("-"), precedence("+"));
+    assert_eq!(precedence("x"), None);
+    assert_eq!(precedence(","), None);
+}
+
+#[test]
+fn test_carriage_returns() {
+    assert!(evaluate_str("1\r+\r2 *\r\n3\r").is_ok_and(|x| x == 7.0));
+    assert!(evaluate_str("gcd(\r12,\r18)").is_ok_and(|x| x == 6.0));
+}
+
+#[test]
+fn test_double_star_exponent() {
+    assert!(evaluate_str("2 ** 10").is_ok_and(|x| x == 1024.0));
+    assert!(evaluate_str("2 ** 3 ** 2").is_ok_and(|x| x == 512.0));
+    assert!(evaluate_str("2**-1").is_ok_and(|x| x == 0.5));
+    assert!(parse("2 * * 3").is_err());
+}
+
+#[test]
+fn test_evaluate_typed() {
+    let root = Node::from_expression("2 + 2".to_string());
+    assert_eq!(root.evaluate_typed().unwrap(), (4.0, true));
+    let root = Node::from_expression("1 / 2".to_string());
+    assert_eq!(root.evaluate_typed().unwrap(), (0.5, false));
+}
+
+#[test]
+fn test_supported_operators() {
+    for operator in SUPPORTED_OPERATORS {
+        assert!(precedence(operator).is_some());
+        assert!(evaluate_str(&format!("6 {} 2", operator)).is_ok());
+    }
+}
+
+#[test]
+fn test_to_json() {
+    assert_eq!(
+        Node::from_expression("1 + 2".to_string()).to_json(),
+        r#"{"value":"+","left":{"value":"1","left":null,"right":null},"right":{"value":"2","left":null,"right":null}}"#
+    );
+    assert_eq!(
+        Node::leaf("x").to_json(),
+        r#"{"value":"x","left":null,"right":null}"#
+    );
+    assert_eq!(
+        Node::leaf("a\"b\\c").to_json(),
+        r#"{"value":"a\"b\\c","left":null,"right":null}"#
+    );
+}
+
+#[test]
+fn test_constants() {
+    let pi = std::f64::consts::PI;
+    assert!(evaluate_str("tau").is_ok_and(|x| x == std::f64::consts::TAU));
+    assert!(evaluate_str("tau / 2").is_ok_and(|x| approx_eq(x, pi, 1e-12)));
+    assert!(evaluate_str("2 * pi - tau").is_ok_and(|x| x == 0.0));
+    assert!(evaluate_str("ln(e)").is_ok_and(|x| x == 1.0));
+}
+
+#[test]
+fn test_evaluate_checked() {
+    let root = Node::from_expression("1 / 0".to_string());
+    assert!(root.evaluate_checked().is_err());
+
+    let root = Node::from_expression("0 ^ -1".to_string());
+    assert!(root.evaluate().is_ok_and(|x| x.is_infinite()));
+    assert!(matches!(root.evaluate_checked(), Err(NodeError::Overflow)));
+
+    let root = Node::from_expression("(-8) ^ 0.5".to_string());
+    assert!(matches!(
+        root.evaluate_checked(),
+        Err(NodeError::DomainError(_))
+    ));
+    assert!(evaluate_str("2 ^ 0.5").is_ok());
+}
+
+#[test]
+fn test_comparisons() {
+    assert!(evaluate_str("1 + 1 == 2").is_ok_and(|x| x == 1.0));
+    assert!(evaluate_str("3 <= 2").is_ok_and(|x| x == 0.0));
+    assert!(evaluate_str("(2 > 1) + (2 != 2)").is_ok_and(|x| x == 1.0));
+}
+
+#[test]
+fn test_chained_comparison() {
+    assert!(evaluate_str("1 < 5 < 10").is_ok_and(|x| x == 1.0));
+    assert!(evaluate_str("1 < 15 < 10").is_ok_and(|x| x == 0.0));
+
+    let root = parse("1 < x <= 10").unwrap();
+    assert_eq!(root.to_infix(), "1 < x && x <= 10");
+    let variables = HashMap::from([("x".to_string(), 10.0)]);
+    assert!(root.evaluate_with(&variables).is_ok_and(|x| x == 1.0));
+}
+
+#[test]
+fn test_literals() {
+    let root = Node::from_expression("2 * 3 + x - 7".to_string());
+    assert_eq!(root.literals(), vec![2.0, 3.0, 7.0]);
+    let root = Node::from_expression("max(pi, -1.5, y)".to_string());
+    assert_eq!(root.literals(), vec![-1.5]);
+}
+
+#[test]
+fn test_evaluate_with_default() {
+    let root = Node::from_expression("x + y".to_string());
+    let variables = HashMap::from([("x".to_string(), 3.0)]);
+    assert!(root
+        .evaluate_with_default(&variables, 0.0)
+        .is_ok_and(|x| x == 3.0));
+    assert!(root.evaluate_with(&variables).is_err());
+
+    let root = Node::from_expression("pi * r + 1 @ 2".to_string());
+    assert!(root.evaluate_with_default(&variables, 0.0).is_err());
+}
+
+#[test]
+fn test_let_bindings() {
+    assert!(evaluate_str("let a = 2 + 3 in a * a").is_ok_and(|x| x == 25.0));
+    assert!(evaluate_str("1 + (let a = 2 in a * 3)").is_ok_and(|x| x == 7.0));
+    assert!(evaluate_str("let a = 1 in let b = a + 1 in a + b").is_ok_and(|x| x == 3.0));
+    assert!(evaluate_str("let a = 1 in let a = 5 in a").is_ok_and(|x| x == 5.0));
+
+    let root = parse("(let x = 2 in x) + x").unwrap();
+    assert_eq!(root.variables(), vec!["x"]);
+    let variables = HashMap::from([("x".to_string(), 10.0)]);
+    assert!(root.evaluate_with(&variables).is_ok_and(|x| x == 12.0));
+    assert_eq!(root.to_infix(), "(let x = 2 in x) + x");
+    assert_eq!(
+        root.substitute("x", &Node::number(1.0)).to_infix(),
+        "(let x = 2 in x) + 1"
+    );
+
+    assert!(parse("let a = 1").is_err());
+    assert!(parse("let = 1 in 2").is_err());
+    assert!(parse("1 = 2").is_err());
+}
+
+#[test]
+fn test_to_mermaid() {
+    let mermaid = parse("1 + 2").unwrap().to_mermaid();
+    assert!(mermaid.starts_with("graph TD"));
+    assert_eq!(
+        mermaid,
+        "graph TD\n    n0[\"+\"]\n    n1[\"1\"]\n    n2[\"2\"]\n    n0 --> n1\n    n0 --> n2\n"
+    );
+    assert!(parse("1 < 2")
+        .unwrap()
+        .to_mermaid()
+        .contains("n0[\"#lt;\"]"));
+}
+
+#[test]
+fn test_evaluate_with_warnings() {
+    let (value, warnings) = parse("0.1 + 0.2")
+        .unwrap()
+        .evaluate_with_warnings()
+        .unwrap();
+    assert_eq!(value, 0.1 + 0.2);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].starts_with("precision"));
+
+    let (_, warnings) = parse("1 + 1e-12")
+        .unwrap()
+        .evaluate_with_warnings()
+        .unwrap();
+    assert!(warnings
+        .iter()
+        .any(|warning| warning.contains("close to the integer 1")));
+
+    let (_, warnings) = parse("2 ^ 60").unwrap().evaluate_with_warnings().unwrap();
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(parse("1 / 4")
+        .unwrap()
+        .evaluate_with_warnings()
+        .unwrap()
+        .1
+        .is_empty());
+    assert!(parse("1 / 0").unwrap().evaluate_with_warnings().is_err());
+    for input in ["1e-300", "5e-324", "-1e-12"] {
+        let (_, warnings) = parse(input).unwrap().evaluate_with_warnings().unwrap();
+        assert!(warnings.is_empty(), "{}: {:?}", input, warnings);
+    }
+}
+
+#[test]
+fn test_evaluate_mod() {
+    assert!(parse("370")
+        .unwrap()
+        .evaluate_mod(360.0)
+        .is_ok_and(|x| x == 10.0));
+    assert!(parse("-30")
+        .unwrap()
+        .evaluate_mod(360.0)
+        .is_ok_and(|x| x == 330.0));
+    assert!(parse("7 * 3")
+        .unwrap()
+        .evaluate_mod(7.0)
+        .is_ok_and(|x| x == 0.0));
+    assert!(parse("-1e-20")
+        .unwrap()
+        .evaluate_mod(360.0)
+        .is_ok_and(|x| x == 0.0));
+    assert!(parse("1").unwrap().evaluate_mod(0.0).is_err());
+    assert!(parse("1").unwrap().evaluate_mod(-5.0).is_err());
+}
+
+#[test]
+fn test_quoted_identifiers() {
+    let root = parse("\"total cost\" * 2").unwrap();
+    assert_eq!(root.variables(), vec!["total cost"]);
+    assert_eq!(root.to_infix(), "\"total cost\" * 2");
+
+    let variables = HashMap::from([("total cost".to_string(), 21.0)]);
+    assert!(root.evaluate_with(&variables).is_ok_and(|x| x == 42.0));
+    assert!(matches!(
+        root.evaluate(),
+        Err(NodeError::UnknownVariable(name)) if name == "total cost"
+    ));
+    assert!(evaluate_str("let \"a b\" = 3 in \"a b\" ^ 2").is_ok_and(|x| x == 9.0));
+    assert!(parse("\"unterminated * 2").is_err());
+    assert!(parse("\"\" + 1").is_err());
+}
+
+#[test]
+fn test_operator_info() {
+    let info = operator_info();
+    assert!(info.contains(&OperatorInfo {
+        symbol: "+",
+        precedence: precedence("+").unwrap(),
+        associativity: Associativity::Left,
+        arity: 2,
+    }));
+    assert!(info
+        .iter()
+        .any(|op| op.symbol == "-" && op.arity == 1 && op.associativity == Associativity::Right));
+    assert!(info
+        .iter()
+        .any(|op| op.symbol == "<=" && op.associativity == Associativity::Chained));
+    assert!(SUPPORTED_OPERATORS
+        .iter()
+        .all(|symbol| info.iter().any(|op| op.symbol == *symbol && op.arity == 2)));
+}
+
+#[test]
+fn test_sum_range() {
+    assert!(evaluate_str("sum_range(1, 5, 1, i)").is_ok_and(|x| x == 15.0));
+    assert!(evaluate_str("sum_range(1, 3, 1, i ^ 2)").is_ok_and(|x| x == 14.0));
+    assert!(evaluate_str("sum_range(10, 1, -3, i)").is_ok_and(|x| x == 22.0));
+    assert!(evaluate_str("sum_range(0, 1, 0.25, 1)").is_ok_and(|x| x == 5.0));
+    assert!(evaluate_str("sum_range(5, 1, 1, i)").is_ok_and(|x| x == 0.0));
+    assert!(evaluate_str("sum_range(1, 2, 0, i)").is_err());
+
+    let root = parse("sum_range(1, n, 1, i * x)").unwrap();
+    assert_eq!(root.variables(), vec!["n", "x"]);
+    let variables = HashMap::from([("n".to_string(), 3.0), ("x".to_string(), 2.0)]);
+    assert!(root.evaluate_with(&variables).is_ok_and(|x| x == 12.0));
+}
+
+#[test]
+fn test_denominators() {
+    assert_eq!(
+        parse("1 / x + 2 / (y - 1)").unwrap().denominators(),
+        vec!["x", "y - 1"]
+    );
+    assert_eq!(
+        parse("a / (b / c)").unwrap().denominators(),
+        vec!["b / c", "c"]
+    );
+    assert!(parse("x * 2").unwrap().denominators().is_empty());
+}
+
+#[test]
+fn test_apply_template() {
+    assert!(apply_template("{0} * {1} + {0}", &[2.0, 5.0]).is_ok_and(|x| x == 12.0));
+    assert!(apply_template("2 ^ {0}", &[-1.0]).is_ok_and(|x| x == 0.5));
+    assert!(apply_template("max({1}, {0})", &[1.5, 1e-3]).is_ok_and(|x| x == 1.5));
+    assert!(apply_template("{2} + 1", &[1.0]).is_err());
+    assert!(apply_template("{x} + 1", &[1.0]).is_err());
+    assert!(apply_template("{0 + 1", &[1.0]).is_err());
+}
+
+#[test]
+fn test_common_subexpressions() {
+    assert_eq!(
+        parse("(a + b) * (a + b)").unwrap().common_subexpressions(),
+        vec!["a + b"]
+    );
+    assert_eq!(
+        parse("sin(x * 2) + (x * 2) ^ 2 - sin(x * 2)")
+            .unwrap()
+            .common_subexpressions(),
+        vec!["x * 2", "sin(x * 2)"]
+    );
+    assert!(parse("a + b + c")
+        .unwrap()
+        .common_subexpressions()
+        .is_empty());
+}
+
+#[test]
+fn test_evaluate_with_decimal_places() {
+    let root = parse("1 / 3 * 3").unwrap();
+    assert!(root
+        .evaluate_with_decimal_places(2)
+        .is_ok_and(|x| x == 0.99));
+    assert!(root.evaluate().is_ok_and(|x| x == 1.0));
+
+    let root = parse("2 / 3 + 2 / 3 - 0.005").unwrap();
+    assert!(root
+        .evaluate_with_decimal_places(2)
+        .is_ok_and(|x| x == 1.34));
+    assert!(parse("0.1 + 0.2")
+        .unwrap()
+        .evaluate_with_decimal_places(2)
+        .is_ok_and(|x| x == 0.3));
+    assert!(parse("1 / 0")
+        .unwrap()
+        .evaluate_with_decimal_places(2)
+        .is_err());
+    assert!(parse("1e307 * 10")
+        .unwrap()
+        .evaluate_with_decimal_places(2)
+        .is_ok_and(|x| x == 1e308));
+    assert!(parse("sqrt(2)")
+        .unwrap()
+        .evaluate_with_decimal_places(2)
+        .is_ok_and(|x| x == 1.41));
+    assert!(parse("sqrt(2) * 100")
+        .unwrap()
+        .evaluate_with_decimal_places(2)
+        .is_ok_and(|x| x == 141.0));
+    assert!(parse("sum_range(1, 3, 1, 1 / 3)")
+        .unwrap()
+        .evaluate_with_decimal_places(2)
+        .is_ok_and(|x| x == 0.99));
+}
+
+#[test]
+fn test_spans() {
+    let root = parse("12 + 34").unwrap();
+    assert_eq!(root.span, Some((3, 4)));
+    assert_eq!(root.l_child.as_ref().unwrap().span, Some((0, 2)));
+    assert_eq!(root.r_child.as_ref().unwrap().span, Some((5, 7)));
+    assert_eq!(parse("sqrt(4) * 2").unwrap().span, Some((8, 9)));
+    assert_eq!(parse("(1 + 2)").unwrap().span, Some((3, 4)));
+    assert_eq!(root, Node::binary("+", Node::leaf("12"), Node::leaf("34")));
+
+    let input = "6 × 2²";
+    let root = parse(input).unwrap();
+    let (start, end) = root.span.unwrap();
+    assert_eq!((start, end), (2, 4));
+    assert_eq!(&input[start..end], "×");
+    let (start, end) = root.r_child.as_ref().unwrap().span.unwrap();
+    assert_eq!(&input[start..end], "²");
+    assert_eq!(Node::from_expression(input.to_string()).span, Some((2, 4)));
+}
+
+#[test]
+fn test_tree_diff() {
+    let diff = |a: &str, b: &str| tree_diff(&parse(a).unwrap(), &parse(b).unwrap());
+    assert_eq!(diff("1 + 2", "1 + 3"), vec!["root.right: 2 -> 3"]);
+    assert_eq!(
+        diff("x * (y + 1)", "x / (y + sqrt(2))"),
+        vec![
+            "root: operator '*' -> '/'",
+            "root.right.right: 1 -> sqrt(2)"
+        ]
+    );
+    assert_eq!(diff("-x", "1 - x"), vec!["root: -x -> 1 - x"]);
+    assert!(diff("(1 + 2)", "1 + 2").is_empty());
 }
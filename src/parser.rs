@@ -0,0 +1,858 @@
+use crate::functions;
+use crate::node::{is_unary_prefix, Node, NodeError, NodeValue};
+use crate::token::{tokenize, Token, TokenKind};
+
+const DEFAULT_MAX_TOKENS: usize = 1_000;
+const MAX_BYTES_PER_TOKEN: usize = 64;
+
+pub struct Parser {
+    symbols: Vec<(String, String)>,
+    max_tokens: usize,
+    implicit_multiplication: bool,
+    left_to_right: bool,
+    strict: bool,
+    max_literal: Option<f64>,
+}
+
+impl Default for Parser {
+    fn default() -> Self {
+        Parser {
+            symbols: vec![
+                ("×".to_string(), "*".to_string()),
+                ("÷".to_string(), "/".to_string()),
+                ("−".to_string(), "-".to_string()),
+            ],
+            max_tokens: DEFAULT_MAX_TOKENS,
+            implicit_multiplication: false,
+            left_to_right: false,
+            strict: false,
+            max_literal: None,
+        }
+    }
+}
+
+impl Parser {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_symbol(mut self, symbol: &str, operator: &str) -> Self {
+        self.symbols.retain(|(existing, _)| existing != symbol);
+        self.symbols
+            .push((symbol.to_string(), operator.to_string()));
+        self
+    }
+
+    pub fn with_exponent_symbol(self, symbol: &str) -> Self {
+        self.with_symbol(symbol, "^")
+    }
+
+    pub fn with_max_tokens(mut self, max_tokens: usize) -> Self {
+        self.max_tokens = max_tokens;
+        self
+    }
+
+    pub fn with_implicit_multiplication(mut self, implicit_multiplication: bool) -> Self {
+        self.implicit_multiplication = implicit_multiplication;
+        self
+    }
+
+    pub fn with_left_to_right(mut self, left_to_right: bool) -> Self {
+        self.left_to_right = left_to_right;
+        self
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    pub fn with_max_literal(mut self, max_literal: f64) -> Self {
+        self.max_literal = Some(max_literal);
+        self
+    }
+
+    pub fn parse(&self, source: &str) -> Result<Node, NodeError> {
+        let too_long = || NodeError::InvalidExpression("expression too long".to_string());
+        if source.len() > self.max_tokens.saturating_mul(MAX_BYTES_PER_TOKEN) {
+            return Err(too_long());
+        }
+
+        let normalized = self.normalize(source);
+        let mut tokens = tokenize(&normalized.text);
+        if self.implicit_multiplication {
+            tokens = insert_implicit_multiplication(tokens);
+        }
+        if tokens.len() > self.max_tokens {
+            return Err(too_long());
+        }
+        if let Some(token) = tokens.iter().find(|token| token.kind == TokenKind::Unknown) {
+            return Err(NodeError::UnexpectedToken {
+                token: token.text.to_string(),
+                position: normalized.position(source, token.start),
+            });
+        }
+
+        check_repeated_operators(&tokens)?;
+        check_keywords(&tokens, |offset| normalized.position(source, offset))?;
+        if self.strict {
+            check_operands(&tokens, |offset| normalized.position(source, offset))?;
+        }
+
+        let mut root = Node::from_tokens_in(&tokens, self.left_to_right);
+        root.map_spans(&|span| normalized.source_span(span));
+        root.validate()?;
+        if let Some(literal) = self
+            .max_literal
+            .and_then(|max_literal| find_large_literal(&root, max_literal))
+        {
+            let (start, end) = literal.span.unwrap_or((0, 0));
+            return Err(NodeError::InvalidExpression(format!(
+                "literal too large: '{}' at position {}",
+                &source[start..end],
+                source[..start].chars().count()
+            )));
+        }
+
+        Ok(root)
+    }
+
+    pub(crate) fn normalize(&self, input: &str) -> Normalized {
+        let mut symbols = self.symbols.iter().collect::<Vec<&(String, String)>>();
+        symbols.sort_by_key(|(symbol, _)| std::cmp::Reverse(symbol.len()));
+        let symbol_for = |text: &str| {
+            symbols
+                .iter()
+                .find(|(symbol, _)| symbol == text)
+                .map(|(_, operator)| operator.as_str())
+        };
+
+        let mut result = Normalized {
+            text: String::with_capacity(input.len()),
+            starts: Vec::with_capacity(input.len()),
+            ends: Vec::with_capacity(input.len()),
+            source_len: input.len(),
+        };
+        let mut in_superscript = false;
+        let mut index = 0;
+        while let Some(c) = input[index..].chars().next() {
+            let rest = &input[index..];
+            let length = if c == '"' {
+                rest[1..].find('"').map_or(rest.len(), |end| end + 2)
+            } else if c.is_alphabetic() || c == '_' {
+                rest.char_indices()
+                    .find(|(_, c)| !is_identifier_char(*c))
+                    .map_or(rest.len(), |(end, _)| end)
+            } else {
+                symbols
+                    .iter()
+                    .find(|(symbol, _)| !symbol.is_empty() && rest.starts_with(symbol.as_str()))
+                    .map_or(c.len_utf8(), |(symbol, _)| symbol.len())
+            };
+            let text = &rest[..length];
+            let span = (index, index + length);
+            index += length;
+
+            if let Some(digit) = superscript(c) {
+                if !in_superscript {
+                    result.push("^", span);
+                }
+                result.push(digit.encode_utf8(&mut [0; 4]), span);
+                in_superscript = true;
+                continue;
+            }
+            in_superscript = false;
+
+            match symbol_for(text) {
+                Some(operator) if c != '"' => result.push(operator, span),
+                _ => {
+                    for (offset, c) in text.char_indices() {
+                        let start = span.0 + offset;
+                        result.push(c.encode_utf8(&mut [0; 4]), (start, start + c.len_utf8()));
+                    }
+                }
+            }
+        }
+        result
+    }
+}
+
+pub(crate) struct Normalized {
+    pub(crate) text: String,
+    starts: Vec<usize>,
+    ends: Vec<usize>,
+    source_len: usize,
+}
+
+impl Normalized {
+    fn push(&mut self, text: &str, (start, end): (usize, usize)) {
+        self.text.push_str(text);
+        self.starts.extend(std::iter::repeat_n(start, text.len()));
+        self.ends.extend(std::iter::repeat_n(end, text.len()));
+    }
+
+    pub(crate) fn source_span(&self, (start, end): (usize, usize)) -> (usize, usize) {
+        let source_start = self.starts.get(start).copied().unwrap_or(self.source_len);
+        match end.checked_sub(1).and_then(|last| self.ends.get(last)) {
+            Some(source_end) if end > start => (source_start, *source_end),
+            _ => (source_start, source_start),
+        }
+    }
+
+    pub(crate) fn position(&self, source: &str, offset: usize) -> usize {
+        source[..self.source_span((offset, offset)).0]
+            .chars()
+            .count()
+    }
+}
+
+fn is_identifier_char(c: char) -> bool {
+    (c.is_alphanumeric() || c == '_') && superscript(c).is_none()
+}
+
+fn superscript(c: char) -> Option<char> {
+    match c {
+        '⁰' => Some('0'),
+        '¹' => Some('1'),
+        '²' => Some('2'),
+        '³' => Some('3'),
+        '⁴' => Some('4'),
+        '⁵' => Some('5'),
+        '⁶' => Some('6'),
+        '⁷' => Some('7'),
+        '⁸' => Some('8'),
+        '⁹' => Some('9'),
+        '⁻' => Some('-'),
+        _ => None,
+    }
+}
+
+fn check_repeated_operators(tokens: &[Token]) -> Result<(), NodeError> {
+    for (index, pair) in tokens.windows(2).enumerate() {
+        let [previous, token] = pair else {
+            continue;
+        };
+        if previous.kind == TokenKind::Operator
+            && token.kind == TokenKind::Operator
+            && (!is_unary_prefix(tokens, index + 1)
+                || (token.text == "+" && matches!(previous.text, "+" | "-")))
+        {
+            return Err(NodeError::InvalidExpression(format!(
+                "unexpected operator '{}' after '{}'",
+                token.text, previous.text
+            )));
+        }
+    }
+    Ok(())
+}
+
+fn check_keywords(tokens: &[Token], position: impl Fn(usize) -> usize) -> Result<(), NodeError> {
+    let unexpected = |token: &Token| NodeError::UnexpectedToken {
+        token: token.text.to_string(),
+        position: position(token.start),
+    };
+
+    let mut lets: Vec<(&Token, usize)> = Vec::new();
+    let mut depth = 0;
+    for (index, token) in tokens.iter().enumerate() {
+        match (token.kind, token.text) {
+            (TokenKind::LeftParen, _) => depth += 1,
+            (TokenKind::RightParen, _) => {
+                if let Some((token, _)) = lets.iter().find(|(_, open)| *open == depth) {
+                    return Err(unexpected(token));
+                }
+                depth = depth.saturating_sub(1);
+            }
+            (TokenKind::Keyword, "let") => {
+                let is_binding = matches!(
+                    (tokens.get(index + 1), tokens.get(index + 2)),
+                    (Some(name), Some(assign))
+                        if name.kind == TokenKind::Identifier && assign.kind == TokenKind::Assign
+                );
+                if !is_binding {
+                    return Err(unexpected(token));
+                }
+                lets.push((token, depth));
+            }
+            (TokenKind::Keyword, _) => match lets.pop() {
+                Some((_, open)) if open == depth => {}
+                _ => return Err(unexpected(token)),
+            },
+            _ => {}
+        }
+    }
+
+    match lets.first() {
+        Some((token, _)) => Err(unexpected(token)),
+        None => Ok(()),
+    }
+}
+
+fn find_large_literal(node: &Node, max_literal: f64) -> Option<&Node> {
+    match node.value {
+        NodeValue::Number(value) if !node.has_children() => {
+            (!value.is_finite() || value.abs() > max_literal).then_some(node)
+        }
+        _ => [&node.l_child, &node.r_child]
+            .into_iter()
+            .flatten()
+            .find_map(|child| find_large_literal(child, max_literal)),
+    }
+}
+
+fn check_operands(tokens: &[Token], position: impl Fn(usize) -> usize) -> Result<(), NodeError> {
+    let ends_operand = |token: Option<&Token>| {
+        token.is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Number | TokenKind::Identifier | TokenKind::RightParen
+            )
+        })
+    };
+    let starts_operand = |index: usize| {
+        tokens.get(index).is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::Number
+                    | TokenKind::Identifier
+                    | TokenKind::LeftParen
+                    | TokenKind::Keyword
+            ) || is_unary_prefix(tokens, index)
+        }) && tokens[index].text != "in"
+    };
+
+    for (index, token) in tokens.iter().enumerate() {
+        if token.kind != TokenKind::Operator {
+            continue;
+        }
+        let has_left = is_unary_prefix(tokens, index)
+            || ends_operand(index.checked_sub(1).map(|i| &tokens[i]));
+        if !has_left || !starts_operand(index + 1) {
+            return Err(NodeError::UnexpectedToken {
+                token: token.text.to_string(),
+                position: position(token.start),
+            });
+        }
+    }
+    Ok(())
+}
+
+fn insert_implicit_multiplication(tokens: Vec<Token>) -> Vec<Token> {
+    let mut result: Vec<Token> = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        let juxtaposed = result.last().is_some_and(|last| {
+            matches!(
+                (last.kind, token.kind),
+                (TokenKind::Number, TokenKind::LeftParen)
+                    | (TokenKind::RightParen, TokenKind::Number)
+                    | (TokenKind::RightParen, TokenKind::LeftParen)
+            )
+        });
+        if juxtaposed {
+            result.push(Token {
+                text: "*",
+                start: token.start,
+                end: token.start,
+                kind: TokenKind::Operator,
+            });
+        }
+        result.push(token);
+    }
+    result
+}
+
+pub fn parse_collecting(source: &str) -> (Option<Node>, Vec<NodeError>) {
+    let normalized = Parser::default().normalize(source);
+    let tokens = tokenize(&normalized.text);
+    let unexpected = |token: &Token| NodeError::UnexpectedToken {
+        token: token.text.to_string(),
+        position: normalized.position(source, token.start),
+    };
+
+    let mut errors = Vec::new();
+    let mut expect_operand = true;
+    let mut after_prefix = true;
+    let mut depth = 0;
+    for token in &tokens {
+        let is_prefix = std::mem::replace(&mut after_prefix, false);
+        match token.kind {
+            TokenKind::Unknown => {
+                errors.push(unexpected(token));
+                after_prefix = is_prefix;
+                continue;
+            }
+            TokenKind::Identifier if functions::is_function(token.text) => {
+                if !expect_operand {
+                    errors.push(unexpected(token));
+                }
+                after_prefix = true;
+                expect_operand = true;
+            }
+            TokenKind::Number | TokenKind::Identifier => {
+                if !expect_operand {
+                    errors.push(unexpected(token));
+                }
+                expect_operand = false;
+            }
+            TokenKind::Operator if expect_operand => {
+                if !(is_prefix && (token.text == "-" || token.text == "+")) {
+                    errors.push(unexpected(token));
+                }
+            }
+            TokenKind::Operator => expect_operand = true,
+            TokenKind::LeftParen => {
+                if !expect_operand {
+                    errors.push(unexpected(token));
+                }
+                depth += 1;
+                after_prefix = true;
+                expect_operand = true;
+            }
+            TokenKind::RightParen if depth == 0 || expect_operand => {
+                errors.push(unexpected(token));
+                depth = 0.max(depth - 1);
+                expect_operand = false;
+            }
+            TokenKind::RightParen => depth -= 1,
+            TokenKind::Comma => {
+                if depth == 0 || expect_operand {
+                    errors.push(unexpected(token));
+                }
+                after_prefix = true;
+                expect_operand = true;
+            }
+            TokenKind::Keyword | TokenKind::Assign => {
+                after_prefix = true;
+                expect_operand = true;
+            }
+        }
+    }
+
+    if depth > 0 {
+        errors.push(NodeError::InvalidExpression(format!(
+            "{} unclosed '('",
+            depth
+        )));
+    }
+    if expect_operand {
+        errors.push(NodeError::InvalidExpression(
+            "missing operand at the end".to_string(),
+        ));
+    }
+
+    if errors.is_empty() {
+        return match Parser::default().parse(source) {
+            Ok(root) => (Some(root), errors),
+            Err(err) => (None, vec![err]),
+        };
+    }
+
+    let tokens = tokens
+        .into_iter()
+        .filter(|token| token.kind != TokenKind::Unknown)
+        .collect::<Vec<Token>>();
+    let best_effort = (depth == 0 && !tokens.is_empty()).then(|| Node::from_tokens(&tokens));
+    (best_effort, errors)
+}
+
+pub fn is_complete(input: &str) -> bool {
+    let normalized = Parser::new().normalize(input);
+    let tokens = tokenize(&normalized.text);
+    let depth = tokens.iter().fold(0, |depth, token| match token.kind {
+        TokenKind::LeftParen => depth + 1,
+        TokenKind::RightParen => depth - 1,
+        _ => depth,
+    });
+
+    depth <= 0
+        && !tokens.last().is_some_and(|last| {
+            matches!(
+                last.kind,
+                TokenKind::Operator | TokenKind::Comma | TokenKind::Assign | TokenKind::Keyword
+            )
+        })
+}
+
+enum Group<'a> {
+    Paren,
+    Call { name: &'a str, arguments: usize },
+    Let { in_body: bool },
+}
+
+pub fn is_valid(input: &str) -> bool {
+    if input.len() > DEFAULT_MAX_TOKENS * MAX_BYTES_PER_TOKEN {
+        return false;
+    }
+    let normalized = Parser::new().normalize(input);
+    let tokens = tokenize(&normalized.text);
+    if tokens.len() > DEFAULT_MAX_TOKENS || check_repeated_operators(&tokens).is_err() {
+        return false;
+    }
+
+    let close_lets = |groups: &mut Vec<Group>| {
+        while let Some(Group::Let { in_body }) = groups.last() {
+            if !in_body {
+                return false;
+            }
+            groups.pop();
+        }
+        true
+    };
+
+    let mut groups = Vec::new();
+    let mut expect_operand = true;
+    let mut index = 0;
+    while let Some(token) = tokens.get(index) {
+        let next = tokens.get(index + 1);
+        match (token.kind, token.text) {
+            (TokenKind::Number, text) if expect_operand && text.parse::<f64>().is_ok() => {
+                expect_operand = false;
+            }
+            (TokenKind::Identifier, name) if expect_operand && functions::is_function(name) => {
+                match next.map(|next| (next.kind, next.text)) {
+                    Some((TokenKind::LeftParen, _)) => {
+                        groups.push(Group::Call { name, arguments: 0 });
+                        index += 1;
+                    }
+                    Some(
+                        (TokenKind::Number | TokenKind::Identifier, _)
+                        | (TokenKind::Keyword, "let"),
+                    ) if functions::check_arity(name, 1).is_ok() => {}
+                    Some((TokenKind::Operator, _)) if is_unary_prefix(&tokens, index + 1) => {
+                        if functions::check_arity(name, 1).is_err() {
+                            return false;
+                        }
+                    }
+                    _ => expect_operand = false,
+                }
+            }
+            (TokenKind::Identifier, _) if expect_operand => expect_operand = false,
+            (TokenKind::Operator, _) if !expect_operand => expect_operand = true,
+            (TokenKind::Operator, _) if is_unary_prefix(&tokens, index) => {}
+            (TokenKind::LeftParen, _) if expect_operand => groups.push(Group::Paren),
+            (TokenKind::RightParen, _) if !expect_operand && close_lets(&mut groups) => {
+                match groups.pop() {
+                    Some(Group::Paren) => {}
+                    Some(Group::Call { name, arguments }) => {
+                        if functions::check_arity(name, arguments + 1).is_err() {
+                            return false;
+                        }
+                    }
+                    _ => return false,
+                }
+            }
+            (TokenKind::Comma, _) if !expect_operand && close_lets(&mut groups) => {
+                match groups.last_mut() {
+                    Some(Group::Call { arguments, .. }) => *arguments += 1,
+                    _ => return false,
+                }
+                expect_operand = true;
+            }
+            (TokenKind::Keyword, "let") if expect_operand => {
+                let name = next.filter(|name| name.kind == TokenKind::Identifier);
+                let assign = tokens.get(index + 2);
+                if name.is_none() || assign.is_none_or(|assign| assign.kind != TokenKind::Assign) {
+                    return false;
+                }
+                groups.push(Group::Let { in_body: false });
+                index += 2;
+            }
+            (TokenKind::Keyword, "in") if !expect_operand => {
+                while let Some(Group::Let { in_body: true }) = groups.last() {
+                    groups.pop();
+                }
+                match groups.last_mut() {
+                    Some(Group::Let { in_body }) => *in_body = true,
+                    _ => return false,
+                }
+                expect_operand = true;
+            }
+            _ => return false,
+        }
+        index += 1;
+    }
+
+    !expect_operand && close_lets(&mut groups) && groups.is_empty()
+}
+
+#[test]
+fn test_default_symbols() {
+    let root = Parser::new().parse("6 × 2 ÷ 3").unwrap();
+    assert!(root.evaluate().is_ok_and(|x| x == 4.0));
+    assert!(Parser::new()
+        .parse("5 − 7")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == -2.0));
+}
+
+#[test]
+fn test_superscripts() {
+    assert!(crate::evaluate_str("5²").is_ok_and(|x| x == 25.0));
+    assert!(crate::evaluate_str("2¹⁰").is_ok_and(|x| x == 1024.0));
+    assert!(crate::evaluate_str("(1 + 1)³ + 4⁻¹").is_ok_and(|x| x == 8.25));
+    assert!(Node::from_expression("x²".to_string())
+        .evaluate_with(&std::collections::HashMap::from([("x".to_string(), 3.0)]))
+        .is_ok_and(|x| x == 9.0));
+}
+
+#[test]
+fn test_custom_symbol() {
+    let parser = Parser::new().with_symbol("x", "*");
+    let root = parser.parse("3 x 4").unwrap();
+    assert!(root.evaluate().is_ok_and(|x| x == 12.0));
+    assert!(parser
+        .parse("max(1, 2) x 3")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 6.0));
+    assert!(parser.parse("xs + \"a x b\"").unwrap().variables() == vec!["a x b", "xs"]);
+
+    let parser = Parser::new().with_exponent_symbol("x");
+    assert!(parser
+        .parse("exp(0) + 2 x 3")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 9.0));
+}
+
+#[test]
+fn test_exponent_symbol() {
+    let parser = Parser::new().with_exponent_symbol("@");
+    assert!(parser
+        .parse("2 @ 3")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 8.0));
+    assert!(parser
+        .parse("2 ^ 3")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 8.0));
+    assert!(Parser::new().parse("2 @ 3").is_err());
+    assert!(Parser::new()
+        .parse("2 ^ 3")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 8.0));
+}
+
+#[test]
+fn test_repeated_operators() {
+    assert!(matches!(
+        Parser::new().parse("1 ++ 2"),
+        Err(NodeError::InvalidExpression(msg)) if msg == "unexpected operator '+' after '+'"
+    ));
+    assert!(matches!(
+        Parser::new().parse("1 * / 2"),
+        Err(NodeError::InvalidExpression(msg)) if msg == "unexpected operator '/' after '*'"
+    ));
+    assert!(matches!(
+        Parser::new().parse("1 - + 2"),
+        Err(NodeError::InvalidExpression(msg)) if msg == "unexpected operator '+' after '-'"
+    ));
+    assert!(Parser::new().parse("2 * -x").is_ok());
+    assert!(Parser::new().parse("2 - - 3").is_ok());
+    assert!(Parser::new()
+        .parse("2 * +3")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 6.0));
+    assert!(Parser::new().parse("2 ^ +x").is_ok());
+    assert!(Parser::new().parse("2 ** 3").is_ok());
+}
+
+#[test]
+fn test_left_to_right() {
+    let parser = Parser::new().with_left_to_right(true);
+    let evaluate = |input: &str| parser.parse(input).and_then(|root| root.evaluate());
+    assert!(evaluate("5 + 3 * 2").is_ok_and(|x| x == 16.0));
+    assert!(evaluate("2 ^ 3 ^ 2").is_ok_and(|x| x == 64.0));
+    assert!(evaluate("-5 + 3 * (1 + 1 * 2)").is_ok_and(|x| x == -8.0));
+    assert!(evaluate("max(1 + 2 * 3, 4)").is_ok_and(|x| x == 9.0));
+    assert!(Parser::new()
+        .parse("5 + 3 * 2")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 11.0));
+}
+
+#[test]
+fn test_strict() {
+    let parser = Parser::new().with_strict(true);
+    assert!(matches!(
+        parser.parse("1 + "),
+        Err(NodeError::UnexpectedToken { token, position: 2 }) if token == "+"
+    ));
+    assert!(matches!(
+        parser.parse(" * 2"),
+        Err(NodeError::UnexpectedToken { token, position: 1 }) if token == "*"
+    ));
+    assert!(matches!(
+        parser.parse("max(1 -, 2)"),
+        Err(NodeError::UnexpectedToken { token, position: 6 }) if token == "-"
+    ));
+    assert!(parser.parse("-(1 + 2) * -x ^ 2").is_ok());
+    assert!(parser.parse("let a = -1 in a * 2").is_ok());
+    assert!(Node::from_expression("1 + ".to_string())
+        .evaluate()
+        .is_ok_and(|x| x == 1.0));
+}
+
+#[test]
+fn test_max_literal() {
+    let parser = Parser::new().with_max_literal(1e6);
+    assert!(matches!(
+        parser.parse("1e9"),
+        Err(NodeError::InvalidExpression(msg)) if msg == "literal too large: '1e9' at position 0"
+    ));
+    assert!(matches!(
+        parser.parse("2 * -1e9"),
+        Err(NodeError::InvalidExpression(msg)) if msg == "literal too large: '-1e9' at position 4"
+    ));
+    assert!(matches!(
+        parser.parse("1 + inf"),
+        Err(NodeError::InvalidExpression(msg)) if msg == "literal too large: 'inf' at position 4"
+    ));
+    assert!(parser.parse("nan").is_err());
+    assert!(parser
+        .parse("500000")
+        .unwrap()
+        .evaluate()
+        .is_ok_and(|x| x == 500_000.0));
+    assert!(parser.parse("1000 ^ 3").is_ok());
+    assert!(Parser::new().parse("1e9").is_ok());
+}
+
+#[test]
+fn test_bare_keywords() {
+    for (input, token, position) in [
+        ("let", "let", 0),
+        ("in", "in", 0),
+        ("1 + in", "in", 4),
+        ("let a = 1", "let", 0),
+        ("(let a = 1) in a", "let", 1),
+        ("let a = 1 in a in 2", "in", 15),
+        ("2 × let", "let", 4),
+    ] {
+        assert!(
+            matches!(
+                Parser::new().parse(input),
+                Err(NodeError::UnexpectedToken { token: t, position: p }) if t == token && p == position
+            ),
+            "{}",
+            input
+        );
+        assert!(!is_valid(input), "{}", input);
+    }
+    assert!(Parser::new().parse("let a = (let b = 1 in b) in a").is_ok());
+}
+
+#[test]
+fn test_max_tokens() {
+    let parser = Parser::new().with_max_tokens(5);
+    assert!(parser.parse("1 + 2 * 3").is_ok());
+    assert!(matches!(
+        parser.parse("1 + 2 * 3 @"),
+        Err(NodeError::InvalidExpression(msg)) if msg == "expression too long"
+    ));
+
+    let long = vec!["1"; DEFAULT_MAX_TOKENS / 2 + 1].join(" + ");
+    assert!(Parser::new().parse(&long).is_err());
+    assert!(Parser::new().parse(&long[..long.len() - 4]).is_ok());
+
+    let padded = format!("1{}", " ".repeat(DEFAULT_MAX_TOKENS * MAX_BYTES_PER_TOKEN));
+    assert!(matches!(
+        Parser::new().parse(&padded),
+        Err(NodeError::InvalidExpression(msg)) if msg == "expression too long"
+    ));
+    assert!(!is_valid(&padded));
+    assert!(Parser::new().parse(&padded[..padded.len() - 1]).is_ok());
+}
+
+#[test]
+fn test_implicit_multiplication() {
+    let parser = Parser::new().with_implicit_multiplication(true);
+    let evaluate = |input: &str| parser.parse(input).and_then(|root| root.evaluate());
+    assert!(evaluate("3(4 + 5)").is_ok_and(|x| x == 27.0));
+    assert!(evaluate("(1 + 2)(3 + 4)").is_ok_and(|x| x == 21.0));
+    assert!(evaluate("(1 + 1)3 - sqrt(4)").is_ok_and(|x| x == 4.0));
+    assert!(evaluate("2 3").is_err());
+    assert!(Parser::new().parse("3(4 + 5)").is_err());
+}
+
+#[test]
+fn test_parse_collecting() {
+    let (root, errors) = parse_collecting("(1 + * 2");
+    assert!(root.is_none());
+    assert_eq!(errors.len(), 2);
+    assert!(matches!(
+        &errors[0],
+        NodeError::UnexpectedToken { token, position: 5 } if token == "*"
+    ));
+    assert!(matches!(&errors[1], NodeError::InvalidExpression(msg) if msg == "1 unclosed '('"));
+
+    let (root, errors) = parse_collecting("2 @ * 3 $");
+    assert_eq!(errors.len(), 2);
+    assert!(root.is_some_and(|root| root.evaluate().is_ok_and(|x| x == 6.0)));
+
+    let (root, errors) = parse_collecting("max(1, -2) * 3");
+    assert!(errors.is_empty());
+    assert!(root.is_some_and(|root| root.evaluate().is_ok_and(|x| x == 3.0)));
+
+    let (_, errors) = parse_collecting("1 +) 2 -");
+    assert_eq!(errors.len(), 3);
+}
+
+#[test]
+fn test_is_complete() {
+    assert!(!is_complete("1 +"));
+    assert!(!is_complete("( 1 + 2"));
+    assert!(!is_complete("max(1,"));
+    assert!(!is_complete("1 ×"));
+    assert!(!is_complete("let a ="));
+    assert!(!is_complete("let a = 1 in"));
+    assert!(is_complete("let a = 1 in a"));
+    assert!(is_complete("1 + 2"));
+    assert!(is_complete("(1 + 2) * 3"));
+}
+
+#[test]
+fn test_is_valid() {
+    assert!(is_valid("1 + 2"));
+    assert!(is_valid("-x * (2 - -3)"));
+    assert!(is_valid("sqrt 4 + max(1, sin(x), 3)"));
+    assert!(!is_valid("1 + + 2"));
+    assert!(!is_valid("1 +"));
+    assert!(!is_valid("(1, 2)"));
+    assert!(!is_valid("(1 + 2"));
+    assert!(!is_valid("1 + 2)"));
+    assert!(!is_valid("1 2"));
+    assert!(!is_valid(""));
+
+    for input in ["6 × 2", "5²", "let a = 1 in a", "2 * +3", "max(1, 2) ^ -x"] {
+        assert!(is_valid(input), "{}", input);
+        assert!(Parser::new().parse(input).is_ok(), "{}", input);
+    }
+    for input in [
+        "max",
+        "sqrt -4",
+        "max()",
+        "pow(1)",
+        "let a = let b = 1 in b in a * 2",
+        "let a = (1 in a)",
+        "(let a = 1 in a) + 1",
+        "1.2.3",
+        "x(1)",
+        "max(, 1)",
+        "max(1, , 2)",
+        "sqrt let a = 4 in a",
+    ] {
+        assert_eq!(
+            is_valid(input),
+            Parser::new().parse(input).is_ok(),
+            "{}",
+            input
+        );
+    }
+}
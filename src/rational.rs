@@ -0,0 +1,128 @@
+use crate::node::{Node, NodeError, NodeValue};
+
+use num_rational::Rational64;
+use num_traits::{CheckedAdd, CheckedDiv, CheckedMul, CheckedSub, One, Zero};
+
+fn checked(result: Option<Rational64>) -> Result<Rational64, NodeError> {
+    result.ok_or(NodeError::Overflow)
+}
+
+fn to_rational(value: f64) -> Option<Rational64> {
+    let (mut previous, mut current) = ((0_i64, 1_i64), (1_i64, 0_i64));
+    let mut remainder = value;
+    while remainder.is_finite() && remainder.abs() < i64::MAX as f64 {
+        let term = remainder.floor();
+        let numer = (term as i64)
+            .checked_mul(current.0)?
+            .checked_add(previous.0)?;
+        let denom = (term as i64)
+            .checked_mul(current.1)?
+            .checked_add(previous.1)?;
+        if numer as f64 / denom as f64 == value {
+            return Some(Rational64::new(numer, denom));
+        }
+        (previous, current) = (current, (numer, denom));
+        remainder = 1.0 / (remainder - term);
+    }
+    None
+}
+
+fn pow(base: Rational64, exponent: Rational64) -> Result<Rational64, NodeError> {
+    if !exponent.is_integer() {
+        return Err(NodeError::DomainError(format!(
+            "rational exponents must be integers, got {}",
+            exponent
+        )));
+    }
+
+    let base = if *exponent.numer() < 0 {
+        checked(Rational64::one().checked_div(&base))?
+    } else {
+        base
+    };
+
+    let mut result = Rational64::one();
+    let (mut base, mut exponent) = (base, exponent.numer().unsigned_abs());
+    while exponent > 0 {
+        if exponent % 2 == 1 {
+            result = checked(result.checked_mul(&base))?;
+        }
+        exponent /= 2;
+        if exponent > 0 {
+            base = checked(base.checked_mul(&base))?;
+        }
+    }
+    Ok(result)
+}
+
+impl Node {
+    pub fn evaluate_rational(&self) -> Result<Rational64, NodeError> {
+        if !self.has_children() {
+            return match self.value {
+                NodeValue::Number(value) => to_rational(value)
+                    .ok_or_else(|| NodeError::InvalidExpression(value.to_string())),
+                _ => Err(NodeError::InvalidExpression(self.value.to_string())),
+            };
+        }
+
+        let l_operand = match &self.l_child {
+            None => Rational64::zero(),
+            Some(l_child) => l_child.evaluate_rational()?,
+        };
+        let r_operand = match &self.r_child {
+            None => Rational64::zero(),
+            Some(r_child) => r_child.evaluate_rational()?,
+        };
+
+        match self.operator() {
+            "+" => checked(l_operand.checked_add(&r_operand)),
+            "-" => checked(l_operand.checked_sub(&r_operand)),
+            "*" => checked(l_operand.checked_mul(&r_operand)),
+            "/" if r_operand.is_zero() => Err(NodeError::DivideByZero {
+                expression: self.to_infix(),
+            }),
+            "/" => checked(l_operand.checked_div(&r_operand)),
+            "^" if l_operand.is_zero() && *r_operand.numer() < 0 => Err(NodeError::DivideByZero {
+                expression: self.to_infix(),
+            }),
+            "^" => pow(l_operand, r_operand),
+            operator => Err(NodeError::InvalidExpression(format!(
+                "'{}' is not supported in rational evaluation",
+                operator
+            ))),
+        }
+    }
+}
+
+#[test]
+fn test_rational_sum() {
+    let root = Node::from_expression("1 / 3 + 1 / 6".to_string());
+    assert_eq!(root.evaluate_rational().unwrap(), Rational64::new(1, 2));
+    assert_eq!(root.evaluate_rational().unwrap().to_string(), "1/2");
+    assert_eq!(
+        Node::from_expression("0.1 + 0.2".to_string())
+            .evaluate_rational()
+            .unwrap(),
+        Rational64::new(3, 10)
+    );
+}
+
+#[test]
+fn test_rational_power_and_division_by_zero() {
+    let root = Node::from_expression("(2 / 3) ^ -2".to_string());
+    assert_eq!(root.evaluate_rational().unwrap(), Rational64::new(9, 4));
+
+    let root = Node::from_expression("1 / (0.5 - 1 / 2)".to_string());
+    assert!(matches!(
+        root.evaluate_rational(),
+        Err(NodeError::DivideByZero { .. })
+    ));
+    let root = Node::from_expression("0 ^ -1".to_string());
+    assert!(matches!(
+        root.evaluate_rational(),
+        Err(NodeError::DivideByZero { .. })
+    ));
+    assert!(Node::from_expression("0 ^ 2".to_string())
+        .evaluate_rational()
+        .is_ok_and(|x| x.is_zero()));
+}
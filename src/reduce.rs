@@ -0,0 +1,316 @@
+use crate::node::{parse, Node, NodeError, NodeValue};
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reduced {
+    Value(f64),
+    Tree(Node),
+}
+
+impl Node {
+    pub fn reduce(&self) -> Result<Reduced, NodeError> {
+        let node = self.fold()?;
+        match node.value {
+            NodeValue::Number(value) if !node.has_children() => Ok(Reduced::Value(value)),
+            _ => Ok(Reduced::Tree(node)),
+        }
+    }
+
+    fn fold(&self) -> Result<Node, NodeError> {
+        if self.variables().is_empty() && self.operator() != "," {
+            return Ok(Node::number(self.evaluate()?));
+        }
+
+        let operator = self.operator();
+        if (operator == "+" || operator == "*") && self.l_child.is_some() {
+            let mut operands = Vec::new();
+            self.collect_chain(operator, &mut operands);
+
+            let mut constants = Vec::new();
+            let mut folded = None;
+            for operand in operands {
+                let operand = operand.fold()?;
+                if let (NodeValue::Number(_), false) = (&operand.value, operand.has_children()) {
+                    constants.push(operand);
+                    continue;
+                }
+                folded = Some(match folded {
+                    None => operand,
+                    Some(folded) => Node::binary(operator, folded, operand),
+                });
+            }
+
+            let folded = folded.expect("a chain with variables has a non-constant operand");
+            return Ok(
+                match constants
+                    .into_iter()
+                    .reduce(|l, r| Node::binary(operator, l, r))
+                {
+                    None => folded,
+                    Some(constants) => {
+                        Node::binary(operator, folded, Node::number(constants.evaluate()?))
+                    }
+                },
+            );
+        }
+
+        let fold_child = |child: &Option<Box<Node>>| match child {
+            None => Ok(None),
+            Some(child) => child.fold().map(|child| Some(Box::new(child))),
+        };
+        Ok(Node {
+            value: self.value.clone(),
+            l_child: fold_child(&self.l_child)?,
+            r_child: fold_child(&self.r_child)?,
+            span: self.span,
+        })
+    }
+
+    pub fn foldable_subexpressions(&self) -> Vec<String> {
+        let mut foldable = Vec::new();
+        self.collect_foldable(&mut foldable);
+        foldable
+    }
+
+    fn collect_foldable(&self, foldable: &mut Vec<String>) {
+        if !self.has_children() {
+            return;
+        }
+        if self.operator() != "," && self.variables().is_empty() {
+            foldable.push(self.to_infix());
+            return;
+        }
+
+        let operator = self.operator();
+        let mut operands = Vec::new();
+        if (operator == "+" || operator == "*") && self.l_child.is_some() {
+            self.collect_chain(operator, &mut operands);
+        } else {
+            operands.extend(self.l_child.as_deref());
+            operands.extend(self.r_child.as_deref());
+        }
+        for operand in operands {
+            operand.collect_foldable(foldable);
+        }
+    }
+
+    fn reduce_step(&self) -> Result<Option<Node>, NodeError> {
+        if !self.has_children() {
+            return Ok(None);
+        }
+
+        if let (Some((name, value)), Some(body)) = (self.binding(), &self.r_child) {
+            return Ok(Some(match value.reduce_step()? {
+                Some(reduced) => {
+                    let mut node = self.clone();
+                    if let Some(assign) = &mut node.l_child {
+                        assign.r_child = Some(Box::new(reduced));
+                    }
+                    node
+                }
+                None => body.substitute(name, value),
+            }));
+        }
+        if self.operator() == "sum_range" {
+            return Ok(Some(Node::number(self.evaluate()?)));
+        }
+
+        if let Some(l_child) = &self.l_child {
+            if let Some(reduced) = l_child.reduce_step()? {
+                let mut node = self.clone();
+                node.l_child = Some(Box::new(reduced));
+                return Ok(Some(node));
+            }
+        }
+        if let Some(r_child) = &self.r_child {
+            if let Some(reduced) = r_child.reduce_step()? {
+                let mut node = self.clone();
+                node.r_child = Some(Box::new(reduced));
+                return Ok(Some(node));
+            }
+        }
+
+        if self.operator() == "," {
+            return Ok(None);
+        }
+        Ok(Some(Node::number(self.evaluate()?)))
+    }
+
+    pub fn rebalance(&self) -> Node {
+        if let ("+" | "*", Some(_), Some(_)) = (self.operator(), &self.l_child, &self.r_child) {
+            let mut operands = Vec::new();
+            self.collect_chain(self.operator(), &mut operands);
+            let operands = operands
+                .into_iter()
+                .map(Node::rebalance)
+                .collect::<Vec<Node>>();
+            return balance(self.operator(), operands);
+        }
+
+        Node {
+            value: self.value.clone(),
+            l_child: self
+                .l_child
+                .as_ref()
+                .map(|l_child| Box::new(l_child.rebalance())),
+            r_child: self
+                .r_child
+                .as_ref()
+                .map(|r_child| Box::new(r_child.rebalance())),
+            span: self.span,
+        }
+    }
+
+    pub fn depth(&self) -> usize {
+        1 + [&self.l_child, &self.r_child]
+            .into_iter()
+            .flatten()
+            .map(|child| child.depth())
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn collect_chain<'a>(&'a self, operator: &str, operands: &mut Vec<&'a Node>) {
+        match (&self.l_child, &self.r_child) {
+            (Some(l_child), Some(r_child)) if self.operator() == operator => {
+                l_child.collect_chain(operator, operands);
+                r_child.collect_chain(operator, operands);
+            }
+            _ => operands.push(self),
+        }
+    }
+}
+
+fn balance(operator: &str, mut operands: Vec<Node>) -> Node {
+    if operands.len() == 1 {
+        return operands.remove(0);
+    }
+    let right = operands.split_off(operands.len() / 2);
+    Node::binary(
+        operator,
+        balance(operator, operands),
+        balance(operator, right),
+    )
+}
+
+pub fn reduce_steps(input: &str) -> Result<Vec<String>, NodeError> {
+    let mut node = parse(input)?;
+    let mut steps = vec![node.to_infix()];
+    while let Some(reduced) = node.reduce_step()? {
+        node = reduced;
+        steps.push(node.to_infix());
+    }
+    Ok(steps)
+}
+
+#[test]
+fn test_reduce() {
+    let reduce = |input: &str| Node::from_expression(input.to_string()).reduce().unwrap();
+    assert_eq!(reduce("2 + 3"), Reduced::Value(5.0));
+    assert_eq!(
+        reduce("sqrt(16) * pi"),
+        Reduced::Value(4.0 * std::f64::consts::PI)
+    );
+    assert_eq!(
+        reduce("x + 2 + 3"),
+        Reduced::Tree(Node::binary("+", Node::leaf("x"), Node::leaf("5")))
+    );
+    assert_eq!(
+        reduce("(x + 2) + 3"),
+        Reduced::Tree(Node::binary("+", Node::leaf("x"), Node::leaf("5")))
+    );
+    match reduce("2 * x * 4 - sin(y + 1 * 2)") {
+        Reduced::Tree(tree) => assert_eq!(tree.to_infix(), "x * 8 - sin(y + 2)"),
+        reduced => panic!("unexpected {:?}", reduced),
+    }
+    assert!(Node::from_expression("x + 1 / 0".to_string())
+        .reduce()
+        .is_err());
+}
+
+#[test]
+fn test_reduce_variadic_call() {
+    let root = parse("max(1, 2, x)").unwrap();
+    match root.reduce().unwrap() {
+        Reduced::Tree(tree) => assert_eq!(tree.to_infix(), "max(1, 2, x)"),
+        reduced => panic!("expected a tree, got {:?}", reduced),
+    }
+    match parse("min(1 + 1, x, 2 * 3)").unwrap().reduce().unwrap() {
+        Reduced::Tree(tree) => assert_eq!(tree.to_infix(), "min(2, x, 6)"),
+        reduced => panic!("expected a tree, got {:?}", reduced),
+    }
+}
+
+#[test]
+fn test_reduce_steps() {
+    assert_eq!(
+        reduce_steps("1 + 2 * 3").unwrap(),
+        vec!["1 + 2 * 3", "1 + 6", "7"]
+    );
+    assert_eq!(
+        reduce_steps("(1 + 2) * max(4, 2 ^ 3)").unwrap(),
+        vec![
+            "(1 + 2) * max(4, 2 ^ 3)",
+            "3 * max(4, 2 ^ 3)",
+            "3 * max(4, 8)",
+            "3 * 8",
+            "24"
+        ]
+    );
+    assert_eq!(
+        reduce_steps("let a = 2 + 3 in a * a").unwrap(),
+        vec![
+            "let a = 2 + 3 in a * a",
+            "let a = 5 in a * a",
+            "5 * 5",
+            "25"
+        ]
+    );
+    assert_eq!(
+        reduce_steps("let a = 1 in let b = a + 1 in a + b").unwrap(),
+        vec![
+            "let a = 1 in let b = a + 1 in a + b",
+            "let b = 1 + 1 in 1 + b",
+            "let b = 2 in 1 + b",
+            "1 + 2",
+            "3"
+        ]
+    );
+    assert_eq!(
+        reduce_steps("sum_range(1, 3, 1, i * 2) + 1").unwrap(),
+        vec!["sum_range(1, 3, 1, i * 2) + 1", "12 + 1", "13"]
+    );
+    assert_eq!(reduce_steps("5").unwrap(), vec!["5"]);
+    assert!(reduce_steps("x + 1").is_err());
+}
+
+#[test]
+fn test_foldable_subexpressions() {
+    let foldable = |input: &str| Node::from_expression(input.to_string()).foldable_subexpressions();
+    assert_eq!(foldable("x + 2 * 3 + sin(0)"), vec!["2 * 3", "sin(0)"]);
+    assert_eq!(
+        foldable("max(x, 1 - 2) / (4 ^ 0.5)"),
+        vec!["1 - 2", "4 ^ 0.5"]
+    );
+    assert!(foldable("x * 2 + y").is_empty());
+    assert_eq!(foldable("1 + 2"), vec!["1 + 2"]);
+}
+
+#[test]
+fn test_rebalance() {
+    let chain = (2..=1000).fold(Node::number(1.0), |chain, n| {
+        Node::binary("+", chain, Node::number(n as f64))
+    });
+    assert_eq!(chain.depth(), 1000);
+
+    let balanced = chain.rebalance();
+    assert_eq!(balanced.depth(), 11);
+    assert!(balanced.evaluate().is_ok_and(|x| x == 500_500.0));
+
+    let root = parse("a * b * c * d - (1 + 2 + 3 + 4)").unwrap();
+    assert_eq!(
+        root.rebalance().to_infix(),
+        "a * b * (c * d) - (1 + 2 + (3 + 4))"
+    );
+    assert_eq!(root.depth(), 5);
+    assert_eq!(root.rebalance().depth(), 4);
+}
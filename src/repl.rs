@@ -0,0 +1,209 @@
+use crate::fib;
+use crate::format::{format_error, format_result};
+use crate::node::{parse, Node};
+use crate::parser::is_complete;
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+const ENV_PREFIX: &str = "EXPR_";
+
+pub struct Repl {
+    prompt: String,
+    tree_only: bool,
+    color: bool,
+    variables: HashMap<String, f64>,
+}
+
+impl Repl {
+    pub fn new(prompt: &str) -> Self {
+        Repl {
+            prompt: prompt.to_string(),
+            tree_only: false,
+            color: false,
+            variables: HashMap::new(),
+        }
+    }
+
+    pub fn tree_only(mut self, tree_only: bool) -> Self {
+        self.tree_only = tree_only;
+        self
+    }
+
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    pub fn with_variables(mut self, variables: HashMap<String, f64>) -> Self {
+        self.variables.extend(variables);
+        self
+    }
+
+    fn tree(&self, root: &Node) -> String {
+        if self.color {
+            root.to_string_colored()
+        } else {
+            root.to_string()
+        }
+    }
+
+    pub fn run<R: BufRead, W: Write>(&self, mut input: R, mut output: W) -> io::Result<()> {
+        loop {
+            writeln!(output, "{}", self.prompt)?;
+
+            let Some(mut line) = read(&mut input)? else {
+                return Ok(());
+            };
+            while !line.is_empty() && !is_complete(&line) {
+                writeln!(output, "Continue the expression")?;
+                match read(&mut input)? {
+                    Some(continuation) if !continuation.is_empty() => {
+                        line = format!("{} {}", line, continuation);
+                    }
+                    _ => break,
+                }
+            }
+
+            if line == "end" {
+                return Ok(());
+            }
+
+            if let Some(argument) = line.strip_prefix("fib") {
+                let argument = argument.trim().parse::<usize>().unwrap_or(1);
+                #[cfg(feature = "num-bigint")]
+                writeln!(output, "fib({}) = {}", argument, fib::fib(argument))?;
+                #[cfg(not(feature = "num-bigint"))]
+                match fib::fib_u128(argument) {
+                    Some(value) => writeln!(output, "fib({}) = {}", argument, value)?,
+                    None => writeln!(output, "fib({}) is too large to represent", argument)?,
+                }
+                continue;
+            }
+
+            if self.tree_only {
+                match parse(&line) {
+                    Ok(root) => writeln!(
+                        output,
+                        "The tree representing the operation:\n{}\n{}",
+                        self.tree(&root),
+                        root.to_json()
+                    )?,
+                    Err(err) => writeln!(output, "Error: {}", format_error(&line, &err))?,
+                }
+                continue;
+            }
+
+            match parse(&line).and_then(|root| Ok((root.evaluate_with(&self.variables)?, root))) {
+                Ok((result, root)) => {
+                    writeln!(
+                        output,
+                        "The tree representing the operation:\n{}",
+                        self.tree(&root)
+                    )?;
+
+                    writeln!(
+                        output,
+                        "The entered expression evaluates to: {}",
+                        format_result(result)
+                    )?
+                }
+                Err(err) => writeln!(output, "Error: {}", format_error(&line, &err))?,
+            }
+        }
+    }
+}
+
+pub fn run_repl<R: BufRead, W: Write>(input: R, output: W, prompt: &str) -> io::Result<()> {
+    Repl::new(prompt).run(input, output)
+}
+
+pub fn bindings_from_env<I: IntoIterator<Item = (String, String)>>(
+    vars: I,
+) -> HashMap<String, f64> {
+    vars.into_iter()
+        .filter_map(|(key, value)| {
+            let name = key.strip_prefix(ENV_PREFIX)?.to_lowercase();
+            let value = parse(&value).and_then(|root| root.evaluate()).ok()?;
+            Some((name, value))
+        })
+        .filter(|(name, _)| !name.is_empty())
+        .collect()
+}
+
+fn read<R: BufRead>(input: &mut R) -> io::Result<Option<String>> {
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Ok(None);
+    }
+    Ok(Some(line.trim().to_string()))
+}
+
+#[test]
+fn test_run_repl() {
+    let input = io::Cursor::new("1 + 2\n(4 *\n2)\nfib 10\n1 / 0\n2 $ 3\nend\n3\n");
+    let mut output = Vec::new();
+    run_repl(input, &mut output, "> ").unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert_eq!(output.matches("> \n").count(), 6);
+    assert!(output.contains("+\n|-- 1\n`-- 2\n"));
+    assert!(output.contains("The entered expression evaluates to: 3\n"));
+    assert!(output.contains("Continue the expression\n"));
+    assert!(output.contains("The entered expression evaluates to: 8\n"));
+    assert!(output.contains("fib(10) = 55\n"));
+    assert!(output.contains("Error: Cannot divide by zero in '1 / 0'\n"));
+    assert!(output.contains("Error: Unexpected token '$' at position 2\n2 $ 3\n  ^\n"));
+}
+
+#[test]
+fn test_run_repl_stops_at_end_of_input() {
+    let mut output = Vec::new();
+    Repl::new("> ")
+        .tree_only(true)
+        .run(io::Cursor::new("1 / 0"), &mut output)
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("/\n|-- 1\n`-- 0\n"));
+    assert!(!output.contains("Error"));
+}
+
+#[test]
+fn test_run_repl_with_color() {
+    let mut output = Vec::new();
+    Repl::new("> ")
+        .color(true)
+        .run(io::Cursor::new("1 + 2\n"), &mut output)
+        .unwrap();
+
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.contains("\x1b[33m+\x1b[0m\n"));
+    assert!(output.contains("The entered expression evaluates to: 3\n"));
+}
+
+#[test]
+fn test_bindings_from_env() {
+    let vars = [
+        ("EXPR_X", "3"),
+        ("EXPR_Rate", "2 * 0.5"),
+        ("EXPR_BAD", "1 +"),
+        ("EXPR_", "4"),
+        ("PATH", "/usr/bin"),
+    ]
+    .map(|(key, value)| (key.to_string(), value.to_string()));
+
+    let bindings = bindings_from_env(vars);
+    assert_eq!(
+        bindings,
+        HashMap::from([("x".to_string(), 3.0), ("rate".to_string(), 1.0)])
+    );
+
+    let mut output = Vec::new();
+    Repl::new("> ")
+        .with_variables(bindings)
+        .run(io::Cursor::new("x * rate + 1\n"), &mut output)
+        .unwrap();
+    assert!(String::from_utf8(output)
+        .unwrap()
+        .contains("The entered expression evaluates to: 4\n"));
+}
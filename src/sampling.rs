@@ -0,0 +1,97 @@
+use crate::node::{approx_eq, parse, Node, NodeError};
+use std::collections::HashMap;
+
+#[doc(hidden)]
+pub struct Xorshift(pub u64);
+
+impl Xorshift {
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    pub fn below(&mut self, bound: u64) -> u64 {
+        self.next_u64() % bound
+    }
+}
+
+pub fn probably_equal(a: &Node, b: &Node, samples: usize) -> bool {
+    let mut names = a.variables();
+    names.extend(b.variables());
+    names.sort_unstable();
+    names.dedup();
+
+    let mut random = Xorshift(0x9e37_79b9_7f4a_7c15);
+    for _ in 0..samples {
+        let variables = names
+            .iter()
+            .map(|name| (name.to_string(), random.next_f64() * 20.0 - 10.0))
+            .collect::<HashMap<String, f64>>();
+
+        let agree = match (a.evaluate_with(&variables), b.evaluate_with(&variables)) {
+            (Ok(x), Ok(y)) => approx_eq(x, y, 1e-9) || (x.is_nan() && y.is_nan()),
+            (Err(_), Err(_)) => true,
+            _ => false,
+        };
+        if !agree {
+            return false;
+        }
+    }
+    true
+}
+
+pub fn sample(
+    input: &str,
+    var: &str,
+    start: f64,
+    end: f64,
+    steps: usize,
+) -> Result<Vec<(f64, f64)>, NodeError> {
+    let root = parse(input)?;
+    let mut variables = HashMap::new();
+
+    let mut samples = Vec::with_capacity(steps);
+    for step in 0..steps {
+        let x = match steps {
+            1 => start,
+            _ => start + (end - start) * step as f64 / (steps - 1) as f64,
+        };
+        variables.insert(var.to_string(), x);
+        if let Ok(y) = root.evaluate_with(&variables) {
+            samples.push((x, y));
+        }
+    }
+    Ok(samples)
+}
+
+#[test]
+fn test_probably_equal() {
+    let parse = |input: &str| Node::from_expression(input.to_string());
+    assert!(probably_equal(&parse("x + x"), &parse("2 * x"), 100));
+    assert!(probably_equal(
+        &parse("(x + y) ^ 2"),
+        &parse("x ^ 2 + 2 * x * y + y ^ 2"),
+        100
+    ));
+    assert!(!probably_equal(&parse("x + 1"), &parse("x + 2"), 100));
+    assert!(!probably_equal(&parse("x"), &parse("y"), 100));
+}
+
+#[test]
+fn test_sample() {
+    assert_eq!(
+        sample("x ^ 2", "x", 0.0, 2.0, 3).unwrap(),
+        vec![(0.0, 0.0), (1.0, 1.0), (2.0, 4.0)]
+    );
+    assert_eq!(
+        sample("1 / t", "t", -1.0, 1.0, 3).unwrap(),
+        vec![(-1.0, -1.0), (1.0, 1.0)]
+    );
+    assert!(sample("x +", "x", 0.0, 1.0, 2).is_err());
+}
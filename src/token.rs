@@ -0,0 +1,217 @@
+use crate::functions;
+use crate::node::SUPPORTED_OPERATORS;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TokenKind {
+    Number,
+    Operator,
+    LeftParen,
+    RightParen,
+    Comma,
+    Identifier,
+    Keyword,
+    Assign,
+    Unknown,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub text: &'a str,
+    pub start: usize,
+    pub end: usize,
+    pub kind: TokenKind,
+}
+
+impl Token<'_> {
+    pub(crate) fn value(&self) -> &str {
+        match self.text {
+            "**" => "^",
+            text => text,
+        }
+    }
+}
+
+pub fn tokenize(expression: &str) -> Vec<Token<'_>> {
+    let chars = expression.char_indices().collect::<Vec<(usize, char)>>();
+    let char_at = |index: usize| chars.get(index).map(|(_, c)| *c);
+    let number_end = |mut index: usize| {
+        while index < chars.len() {
+            let is_exponent = matches!(char_at(index), Some('e' | 'E'))
+                && char_at(index + 1).is_some_and(|next| {
+                    next.is_ascii_digit()
+                        || ((next == '-' || next == '+')
+                            && char_at(index + 2).is_some_and(|c| c.is_ascii_digit()))
+                });
+
+            if is_exponent {
+                index += 2;
+            } else if chars[index].1.is_ascii_digit() || chars[index].1 == '.' {
+                index += 1;
+            } else {
+                break;
+            }
+        }
+        index
+    };
+    let is_raised = |mut index: usize| {
+        while char_at(index).is_some_and(char::is_whitespace) {
+            index += 1;
+        }
+        char_at(index) == Some('^')
+            || (char_at(index) == Some('*') && char_at(index + 1) == Some('*'))
+    };
+    let mut tokens: Vec<Token> = Vec::new();
+    let mut index = 0;
+
+    while index < chars.len() {
+        let c = chars[index].1;
+        let start = index;
+
+        if c.is_whitespace() {
+            index += 1;
+            continue;
+        }
+
+        let is_unary_minus = c == '-'
+            && char_at(index + 1).is_some_and(|next| next.is_ascii_digit() || next == '.')
+            && tokens.last().is_none_or(|last| match last.kind {
+                TokenKind::Operator | TokenKind::LeftParen | TokenKind::Comma => true,
+                TokenKind::Identifier => functions::is_function(last.text),
+                _ => false,
+            })
+            && !is_raised(number_end(index + 1));
+
+        let kind = if c.is_ascii_digit() || c == '.' || is_unary_minus {
+            index = number_end(index + 1);
+            TokenKind::Number
+        } else if c.is_alphabetic() || c == '_' {
+            while index < chars.len() && (chars[index].1.is_alphanumeric() || chars[index].1 == '_')
+            {
+                index += 1;
+            }
+            match &expression
+                [chars[start].0..chars.get(index).map_or(expression.len(), |(end, _)| *end)]
+            {
+                "let" | "in" => TokenKind::Keyword,
+                _ => TokenKind::Identifier,
+            }
+        } else if c == '"' {
+            index += 1;
+            while index < chars.len() && chars[index].1 != '"' {
+                index += 1;
+            }
+            if index < chars.len() {
+                index += 1;
+                TokenKind::Identifier
+            } else {
+                TokenKind::Unknown
+            }
+        } else if char_at(index + 1).is_some_and(|next| {
+            let operator = format!("{}{}", c, next);
+            operator == "**" || SUPPORTED_OPERATORS.contains(&operator.as_str())
+        }) {
+            index += 2;
+            TokenKind::Operator
+        } else {
+            index += 1;
+            match c {
+                '(' => TokenKind::LeftParen,
+                ')' => TokenKind::RightParen,
+                ',' => TokenKind::Comma,
+                '=' => TokenKind::Assign,
+                _ if SUPPORTED_OPERATORS.contains(&c.to_string().as_str()) => TokenKind::Operator,
+                _ => TokenKind::Unknown,
+            }
+        };
+
+        let start = chars[start].0;
+        let end = chars.get(index).map_or(expression.len(), |(end, _)| *end);
+        tokens.push(Token {
+            text: &expression[start..end],
+            start,
+            end,
+            kind,
+        });
+    }
+
+    tokens
+}
+
+#[test]
+fn test_token_spans() {
+    let tokens = tokenize("12 + 3");
+    assert_eq!(
+        tokens,
+        vec![
+            Token {
+                text: "12",
+                start: 0,
+                end: 2,
+                kind: TokenKind::Number
+            },
+            Token {
+                text: "+",
+                start: 3,
+                end: 4,
+                kind: TokenKind::Operator
+            },
+            Token {
+                text: "3",
+                start: 5,
+                end: 6,
+                kind: TokenKind::Number
+            },
+        ]
+    );
+}
+
+#[test]
+fn test_token_kinds() {
+    let kinds = tokenize("max(x, -2) ** 2 @")
+        .iter()
+        .map(|token| token.kind)
+        .collect::<Vec<TokenKind>>();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Identifier,
+            TokenKind::LeftParen,
+            TokenKind::Identifier,
+            TokenKind::Comma,
+            TokenKind::Number,
+            TokenKind::RightParen,
+            TokenKind::Operator,
+            TokenKind::Number,
+            TokenKind::Unknown,
+        ]
+    );
+}
+
+#[test]
+fn test_let_tokens() {
+    let kinds = tokenize("let a = 1 in a == 1")
+        .iter()
+        .map(|token| token.kind)
+        .collect::<Vec<TokenKind>>();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenKind::Keyword,
+            TokenKind::Identifier,
+            TokenKind::Assign,
+            TokenKind::Number,
+            TokenKind::Keyword,
+            TokenKind::Identifier,
+            TokenKind::Operator,
+            TokenKind::Number,
+        ]
+    );
+}
+
+#[test]
+fn test_quoted_identifier_token() {
+    let tokens = tokenize("\"total cost\" * 2");
+    assert_eq!(tokens[0].text, "\"total cost\"");
+    assert_eq!(tokens[0].kind, TokenKind::Identifier);
+    assert_eq!(tokenize("\"open")[0].kind, TokenKind::Unknown);
+}
@@ -0,0 +1,224 @@
+use crate::functions;
+use crate::node::{Node, NodeError};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Unit {
+    pub length: i32,
+    pub time: i32,
+    pub mass: i32,
+}
+
+const BASE_UNITS: &[(&str, Unit)] = &[
+    (
+        "m",
+        Unit {
+            length: 1,
+            time: 0,
+            mass: 0,
+        },
+    ),
+    (
+        "s",
+        Unit {
+            length: 0,
+            time: 1,
+            mass: 0,
+        },
+    ),
+    (
+        "kg",
+        Unit {
+            length: 0,
+            time: 0,
+            mass: 1,
+        },
+    ),
+];
+
+impl Unit {
+    pub fn dimensionless() -> Self {
+        Self::default()
+    }
+
+    pub fn from_symbol(symbol: &str) -> Option<Self> {
+        BASE_UNITS
+            .iter()
+            .find(|(base, _)| *base == symbol)
+            .map(|(_, unit)| *unit)
+    }
+
+    pub fn is_dimensionless(&self) -> bool {
+        *self == Self::dimensionless()
+    }
+
+    fn combine(&self, other: &Unit, sign: i32) -> Result<Unit, NodeError> {
+        let combine = |a: i32, b: i32| {
+            b.checked_mul(sign)
+                .and_then(|b| a.checked_add(b))
+                .ok_or(NodeError::Overflow)
+        };
+        Ok(Unit {
+            length: combine(self.length, other.length)?,
+            time: combine(self.time, other.time)?,
+            mass: combine(self.mass, other.mass)?,
+        })
+    }
+
+    fn powi(&self, exponent: i32) -> Result<Unit, NodeError> {
+        let power = |a: i32| a.checked_mul(exponent).ok_or(NodeError::Overflow);
+        Ok(Unit {
+            length: power(self.length)?,
+            time: power(self.time)?,
+            mass: power(self.mass)?,
+        })
+    }
+}
+
+impl std::fmt::Display for Unit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let parts = [("m", self.length), ("kg", self.mass), ("s", self.time)]
+            .iter()
+            .filter(|(_, exponent)| *exponent != 0)
+            .map(|(symbol, exponent)| match exponent {
+                1 => symbol.to_string(),
+                exponent => format!("{}^{}", symbol, exponent),
+            })
+            .collect::<Vec<String>>();
+
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl Node {
+    pub fn evaluate_with_units(&self) -> Result<(f64, Unit), NodeError> {
+        if !self.has_children() {
+            return parse_quantity(&self.value.to_string());
+        }
+
+        if functions::is_function(self.operator()) {
+            let mut arguments = Vec::new();
+            for argument in self.arguments() {
+                let (value, unit) = argument.evaluate_with_units()?;
+                if !unit.is_dimensionless() {
+                    return Err(NodeError::IncompatibleUnits(format!(
+                        "'{}' expects dimensionless arguments, got {}",
+                        self.value, unit
+                    )));
+                }
+                arguments.push(value);
+            }
+
+            return Ok((
+                functions::execute_function(self.operator(), &arguments)?,
+                Unit::dimensionless(),
+            ));
+        }
+
+        let (r_operand, r_unit) = match &self.r_child {
+            None => (0.0, Unit::dimensionless()),
+            Some(r_child) => r_child.evaluate_with_units()?,
+        };
+        let (l_operand, l_unit) = match &self.l_child {
+            None => (0.0, r_unit),
+            Some(l_child) => l_child.evaluate_with_units()?,
+        };
+
+        match self.operator() {
+            "+" | "-" if l_unit != r_unit => Err(NodeError::IncompatibleUnits(format!(
+                "{} {} {}",
+                l_unit, self.value, r_unit
+            ))),
+            "+" => Ok((l_operand + r_operand, l_unit)),
+            "-" => Ok((l_operand - r_operand, l_unit)),
+            "*" => Ok((l_operand * r_operand, l_unit.combine(&r_unit, 1)?)),
+            "/" if r_operand == 0.0 => Err(NodeError::DivideByZero {
+                expression: self.to_infix(),
+            }),
+            "/" => Ok((l_operand / r_operand, l_unit.combine(&r_unit, -1)?)),
+            "^" if !r_unit.is_dimensionless() => Err(NodeError::IncompatibleUnits(format!(
+                "exponents must be dimensionless, got {}",
+                r_unit
+            ))),
+            "^" if l_unit.is_dimensionless() => {
+                Ok((l_operand.powf(r_operand), Unit::dimensionless()))
+            }
+            "^" if r_operand.fract() == 0.0 && r_operand.abs() <= i32::MAX as f64 => {
+                Ok((l_operand.powf(r_operand), l_unit.powi(r_operand as i32)?))
+            }
+            "^" => Err(NodeError::IncompatibleUnits(format!(
+                "cannot raise {} to the non-integer power {}",
+                l_unit, r_operand
+            ))),
+            operator => Err(NodeError::InvalidExpression(format!(
+                "{} {} {}",
+                l_operand, operator, r_operand
+            ))),
+        }
+    }
+}
+
+fn parse_quantity(leaf: &str) -> Result<(f64, Unit), NodeError> {
+    let parts = leaf.split_whitespace().collect::<Vec<&str>>();
+
+    match parts.as_slice() {
+        [number] if number.parse::<f64>().is_ok() => {
+            Ok((number.parse::<f64>().unwrap(), Unit::dimensionless()))
+        }
+        [symbol] if Unit::from_symbol(symbol).is_some() => {
+            Ok((1.0, Unit::from_symbol(symbol).unwrap()))
+        }
+        [number, symbol] => match (number.parse::<f64>(), Unit::from_symbol(symbol)) {
+            (Ok(value), Some(unit)) => Ok((value, unit)),
+            _ => Err(NodeError::InvalidExpression(leaf.to_string())),
+        },
+        _ => Err(NodeError::InvalidExpression(leaf.to_string())),
+    }
+}
+
+#[test]
+fn test_add_units() {
+    let root = Node::from_expression("3 m + 2 m".to_string());
+    let (value, unit) = root.evaluate_with_units().unwrap();
+    assert_eq!(value, 5.0);
+    assert_eq!(unit, Unit::from_symbol("m").unwrap());
+    assert_eq!(format!("{} {}", value, unit), "5 m");
+
+    let root = Node::from_expression("3 m + 2 s".to_string());
+    assert!(matches!(
+        root.evaluate_with_units(),
+        Err(NodeError::IncompatibleUnits(_))
+    ));
+}
+
+#[test]
+fn test_multiply_and_divide_units() {
+    let root = Node::from_expression("m * m".to_string());
+    let (value, unit) = root.evaluate_with_units().unwrap();
+    assert_eq!(value, 1.0);
+    assert_eq!(unit.to_string(), "m^2");
+
+    let root = Node::from_expression("(10 m / 2 s) * 3 kg".to_string());
+    let (value, unit) = root.evaluate_with_units().unwrap();
+    assert_eq!(value, 15.0);
+    assert_eq!(unit.to_string(), "m kg s^-1");
+}
+
+#[test]
+fn test_unit_exponent_overflow() {
+    let root = Node::from_expression("(m * m) ^ 2000000000".to_string());
+    assert!(matches!(
+        root.evaluate_with_units(),
+        Err(NodeError::Overflow)
+    ));
+
+    let root = Node::from_expression("m ^ 2000000000 * m ^ 2000000000".to_string());
+    assert!(matches!(
+        root.evaluate_with_units(),
+        Err(NodeError::Overflow)
+    ));
+    assert!(
+        Node::from_expression("m ^ 2000000000 / m ^ 2000000000".to_string())
+            .evaluate_with_units()
+            .is_ok_and(|(_, unit)| unit.is_dimensionless())
+    );
+}
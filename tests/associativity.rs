@@ -0,0 +1,157 @@
+use calculator_v2::parse;
+use calculator_v2::sampling::Xorshift;
+
+#[derive(Debug)]
+enum Expr {
+    Literal(u32),
+    Negate(Box<Expr>),
+    Binary(char, Box<Expr>, Box<Expr>),
+}
+
+fn generate(random: &mut Xorshift, depth: u32) -> Expr {
+    if depth == 0 || random.below(4) == 0 {
+        return Expr::Literal(random.below(9) as u32 + 1);
+    }
+    if random.below(6) == 0 {
+        return match generate(random, depth - 1) {
+            Expr::Negate(expr) => *expr,
+            expr => Expr::Negate(Box::new(expr)),
+        };
+    }
+
+    let operator = ['+', '-', '*', '/', '^'][random.below(5) as usize];
+    let depth = if operator == '^' { depth.min(2) } else { depth };
+    Expr::Binary(
+        operator,
+        Box::new(generate(random, depth - 1)),
+        Box::new(generate(random, depth - 1)),
+    )
+}
+
+fn reference(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Literal(value) => Some(*value as f64),
+        Expr::Negate(expr) => Some(-reference(expr)?),
+        Expr::Binary(operator, l, r) => {
+            let (l, r) = (reference(l)?, reference(r)?);
+            match operator {
+                '+' => Some(l + r),
+                '-' => Some(l - r),
+                '*' => Some(l * r),
+                '/' if r == 0.0 => None,
+                '/' => Some(l / r),
+                _ => Some(l.powf(r)),
+            }
+        }
+    }
+}
+
+const NEGATE: u8 = 3;
+
+fn precedence(operator: char) -> u8 {
+    match operator {
+        '+' | '-' => 1,
+        '*' | '/' => 2,
+        _ => 4,
+    }
+}
+
+fn fully_parenthesized(expr: &Expr) -> String {
+    match expr {
+        Expr::Literal(value) => value.to_string(),
+        Expr::Negate(expr) => format!("(-{})", fully_parenthesized(expr)),
+        Expr::Binary(operator, l, r) => format!(
+            "({} {} {})",
+            fully_parenthesized(l),
+            operator,
+            fully_parenthesized(r)
+        ),
+    }
+}
+
+fn minimally_parenthesized(expr: &Expr) -> String {
+    let (operator, l, r) = match expr {
+        Expr::Literal(value) => return value.to_string(),
+        Expr::Negate(expr) => {
+            return match expr.as_ref() {
+                Expr::Binary(operator, _, _) if precedence(*operator) < NEGATE => {
+                    format!("-({})", minimally_parenthesized(expr))
+                }
+                _ => format!("-{}", minimally_parenthesized(expr)),
+            }
+        }
+        Expr::Binary(operator, l, r) => (operator, l, r),
+    };
+
+    let operand = |child: &Expr, is_right: bool| {
+        let wrap = match child {
+            Expr::Literal(_) => false,
+            Expr::Negate(_) => !is_right && precedence(*operator) > NEGATE,
+            Expr::Binary(child_operator, _, _) => {
+                let (parent, child) = (precedence(*operator), precedence(*child_operator));
+                let right_associative = *operator == '^';
+                child < parent || (child == parent && is_right != right_associative)
+            }
+        };
+        if wrap {
+            format!("({})", minimally_parenthesized(child))
+        } else {
+            minimally_parenthesized(child)
+        }
+    };
+
+    format!("{} {} {}", operand(l, false), operator, operand(r, true))
+}
+
+fn same_result(actual: Option<f64>, expected: Option<f64>) -> bool {
+    match (actual, expected) {
+        (Some(a), Some(b)) => a == b || (a.is_nan() && b.is_nan()),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
+#[test]
+fn test_unparenthesized_expressions_match_reference() {
+    let mut random = Xorshift(0x2545_f491_4f6c_dd1d);
+    for _ in 0..2000 {
+        let expr = generate(&mut random, 4);
+        let expected = reference(&expr);
+        let (full, minimal) = (fully_parenthesized(&expr), minimally_parenthesized(&expr));
+
+        let full_tree = parse(&full).unwrap();
+        let minimal_tree = parse(&minimal).unwrap();
+        assert_eq!(
+            full_tree, minimal_tree,
+            "'{}' and '{}' differ",
+            full, minimal
+        );
+        assert!(
+            same_result(minimal_tree.evaluate().ok(), expected),
+            "'{}' evaluated to {:?}, expected {:?}",
+            minimal,
+            minimal_tree.evaluate(),
+            expected
+        );
+        assert_eq!(parse(&minimal_tree.to_infix()).unwrap(), minimal_tree);
+    }
+}
+
+#[test]
+fn test_associativity() {
+    let evaluate = |input: &str| parse(input).unwrap().evaluate().unwrap();
+    assert_eq!(evaluate("8 / 2 / 2"), 2.0);
+    assert_eq!(evaluate("1 - 2 - 3"), -4.0);
+    assert_eq!(evaluate("10 - 4 + 3"), 9.0);
+    assert_eq!(evaluate("24 / 4 * 2"), 12.0);
+    assert_eq!(evaluate("2 ^ 3 ^ 2"), 512.0);
+    assert_eq!(evaluate("2 * -(4 - 1)"), -6.0);
+    assert_eq!(evaluate("-(2) ^ 2"), -4.0);
+    assert_eq!(evaluate("-2 ^ 2"), -4.0);
+    assert_eq!(evaluate("-2 ** 2"), -4.0);
+    assert_eq!(evaluate("(-2) ^ 2"), 4.0);
+    assert_eq!(evaluate("2 ^ -2 ^ 2"), 0.0625);
+    assert_eq!(evaluate("3 * -2 ^ 2"), -12.0);
+    assert_eq!(evaluate("-2 * 3"), -6.0);
+    assert_eq!(evaluate("-(3) * 2 + 1"), -5.0);
+}
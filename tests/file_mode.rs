@@ -0,0 +1,32 @@
+use std::process::Command;
+
+#[test]
+fn test_file_mode() {
+    let path = std::env::temp_dir().join(format!("expressions-{}.txt", std::process::id()));
+    std::fs::write(&path, "# sums\n1 + 2\n\ngcd(12, 18) * 2\n").unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_calculator-v2"))
+        .arg(&path)
+        .output()
+        .unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "1 + 2 -> 3\ngcd(12, 18) * 2 -> 12\n"
+    );
+}
+
+#[test]
+fn test_file_mode_missing_file() {
+    let output = Command::new(env!("CARGO_BIN_EXE_calculator-v2"))
+        .arg("does-not-exist.txt")
+        .output()
+        .unwrap();
+
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stderr)
+        .unwrap()
+        .contains("File not found: does-not-exist.txt"));
+}
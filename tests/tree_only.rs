@@ -0,0 +1,38 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+fn run_with_input(args: &[&str], input: &str) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_calculator-v2"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+
+    String::from_utf8(child.wait_with_output().unwrap().stdout).unwrap()
+}
+
+#[test]
+fn test_tree_only_skips_evaluation() {
+    for flag in ["--tree-only", "-t"] {
+        let output = run_with_input(&[flag], "1 / 0\nend\n");
+
+        assert!(output.contains("/\n|-- 1\n`-- 0\n"));
+        assert!(output.contains(r#"{"value":"/","left":"#));
+        assert!(!output.contains("divide by zero"));
+        assert!(!output.contains("evaluates to"));
+    }
+}
+
+#[test]
+fn test_without_tree_only_evaluates() {
+    let output = run_with_input(&[], "1 / 0\nend\n");
+
+    assert!(output.contains("Cannot divide by zero"));
+}